@@ -0,0 +1,589 @@
+use crate::charset::{to_words, Unmappable};
+use crate::command_parser::operation;
+use crate::mixcomputer::MIXComputer;
+use crate::mixcpu::MEMORY_MAX;
+use crate::mixword::{ByteSize, MIXWord};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt::Display;
+
+/// A MIXAL program translated into a memory image.
+pub struct Assembly {
+    /// a machine with the program loaded into its memory.
+    pub computer: MIXComputer,
+    /// the address given to `END`.
+    pub start: usize,
+    /// the listing, which also holds the symbol table.
+    pub listing: Listing,
+}
+
+/// What every source line assembled to, followed by the symbol table.
+pub struct Listing {
+    /// one line per source line up to `END`, then one per literal.
+    pub lines: Vec<ListingLine>,
+    /// the value of every symbol, local symbols excepted.
+    pub symbols: BTreeMap<String, i64>,
+    /// the byte size the words were assembled for.
+    pub byte_size: ByteSize,
+}
+
+/// One line of a listing.
+pub struct ListingLine {
+    /// the source line number, counting from 1.
+    pub number: usize,
+    /// where the word went, or `None` for a line that stores nothing.
+    pub location: Option<usize>,
+    /// the word the line assembled to, or `None` for a line that stores nothing.
+    pub word: Option<MIXWord>,
+    /// the source line, or the text of a literal.
+    pub source: String,
+}
+
+impl Assembly {
+    /// ### Symbols
+    /// the symbol table, owned by the listing.
+    /// ```rust
+    /// use mixe::assembler::assemble;
+    /// let program = assemble(" ORIG 100\nX LDA X\n END X").unwrap();
+    /// assert_eq!(program.symbols()["X"], 100);
+    /// ```
+    pub fn symbols(&self) -> &BTreeMap<String, i64> {
+        &self.listing.symbols
+    }
+}
+
+/// ### Assemble
+/// translate MIXAL source into a loaded `MIXComputer`.
+///
+/// Each line is `LOC OP ADDRESS` separated by blanks; a line starting with a blank has no
+/// label, and a line starting with `*` is a comment. Anything after the address is ignored.
+/// The text of `ALF` is the five characters after the single blank following `ALF`, or a
+/// `"quoted"` string.
+/// ```rust
+/// use mixe::assembler::assemble;
+/// let program = assemble("
+/// X       EQU  1000
+///         ORIG 3000
+/// START   LDA  X
+///         JMP  1F
+/// 1H      HLT
+///         END  START
+/// ").unwrap();
+/// assert_eq!(program.start, 3000);
+/// assert_eq!(program.computer.memory[3001].get_aa(), 3002);
+/// ```
+pub fn assemble(source: &str) -> Result<Assembly, Box<dyn Error>> {
+    assemble_for(source, ByteSize::Binary)
+}
+
+/// ### Assemble for
+/// translate MIXAL source for a machine with the given byte size.
+/// ```rust
+/// use mixe::assembler::assemble_for;
+/// use mixe::mixword::ByteSize;
+/// let program = assemble_for(" LDA 2000,1(1:3)\n END 0", ByteSize::Decimal).unwrap();
+/// assert_eq!(program.computer.memory[0].bytes(ByteSize::Decimal), [0, 20, 0, 1, 11, 8]);
+/// ```
+pub fn assemble_for(source: &str, byte_size: ByteSize) -> Result<Assembly, Box<dyn Error>> {
+    let lines: Vec<Line> = source
+        .lines()
+        .enumerate()
+        .map(|(i, text)| split_line(i + 1, text))
+        .collect();
+    let mut assembler = Assembler {
+        byte_size,
+        ..Default::default()
+    };
+    assembler.define_symbols(&lines)?;
+    assembler.emit(&lines)
+}
+
+/// ### Listing
+/// assemble MIXAL source and return only its listing.
+/// ```rust
+/// use mixe::assembler::listing;
+/// let listing = listing(" ORIG 100\nX LDA X\n END X").unwrap();
+/// assert_eq!(listing.lines[1].location, Some(100));
+/// assert_eq!(listing.symbols["X"], 100);
+/// ```
+pub fn listing(source: &str) -> Result<Listing, Box<dyn Error>> {
+    listing_for(source, ByteSize::Binary)
+}
+
+/// ### Listing for
+/// the listing of MIXAL source assembled for the given byte size.
+/// ```rust
+/// use mixe::assembler::listing_for;
+/// use mixe::mixword::ByteSize;
+/// let listing = listing_for(" ORIG 100\nX LDA X(1:3)\n END X", ByteSize::Decimal).unwrap();
+/// let word = listing.lines[1].word.unwrap();
+/// assert_eq!(word.get_range_for(4, 4, ByteSize::Decimal).get_value(), 11);
+/// ```
+pub fn listing_for(source: &str, byte_size: ByteSize) -> Result<Listing, Box<dyn Error>> {
+    Ok(assemble_for(source, byte_size)?.listing)
+}
+
+impl ListingLine {
+    /// ### Render for
+    /// the line as `LIST` prints it, its word split as `±AA I F C` with bytes of the given size.
+    /// ```rust
+    /// use mixe::assembler::listing_for;
+    /// use mixe::mixword::ByteSize;
+    /// let listing = listing_for(" ORIG 100\n MOVE 3000(70)\n END 100", ByteSize::Decimal).unwrap();
+    /// assert_eq!(
+    ///     listing.lines[1].render_for(ByteSize::Decimal),
+    ///     "   2  0100: + 3000 00 70 07    MOVE 3000(70)"
+    /// );
+    /// ```
+    pub fn render_for(&self, size: ByteSize) -> String {
+        let location = match self.location {
+            Some(location) => format!("{:04}: ", location),
+            None => " ".repeat(6),
+        };
+        let word = match self.word {
+            Some(word) => {
+                let [sign, a1, a2, i, field, c] = word.bytes(size);
+                format!(
+                    "{} {:04} {:02} {:02} {:02}",
+                    if sign == 1 { '-' } else { '+' },
+                    a1 * size.base() as u32 + a2,
+                    i,
+                    field,
+                    c
+                )
+            }
+            None => " ".repeat(17),
+        };
+        format!("{:4}  {}{}   {}", self.number, location, word, self.source)
+    }
+}
+
+/// a line shows as [`ListingLine::render_for`] renders it with binary bytes.
+impl Display for ListingLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render_for(ByteSize::Binary))
+    }
+}
+
+impl Display for Listing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line.render_for(self.byte_size))?;
+        }
+        writeln!(f)?;
+        writeln!(f, "SYMBOL TABLE")?;
+        for (symbol, value) in &self.symbols {
+            writeln!(f, "{:10} {}", symbol, value)?;
+        }
+        Ok(())
+    }
+}
+
+struct Line<'a> {
+    number: usize,
+    text: &'a str,
+    label: &'a str,
+    op: &'a str,
+    operand: &'a str,
+}
+
+/// split a line into its fields; comments and blank lines have an empty `op`.
+fn split_line(number: usize, text: &str) -> Line<'_> {
+    if text.trim().is_empty() || text.starts_with('*') {
+        return Line {
+            number,
+            text,
+            label: "",
+            op: "",
+            operand: "",
+        };
+    }
+    let (label, rest) = if text.starts_with(char::is_whitespace) {
+        ("", text)
+    } else {
+        text.split_once(char::is_whitespace).unwrap_or((text, ""))
+    };
+    let rest = rest.trim_start();
+    let (op, rest) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let operand = if op == "ALF" {
+        alf_operand(rest)
+    } else {
+        rest.split_whitespace().next().unwrap_or("")
+    };
+    Line {
+        number,
+        text,
+        label,
+        op,
+        operand,
+    }
+}
+
+fn alf_operand(rest: &str) -> &str {
+    if let Some(quoted) = rest.trim_start().strip_prefix('"') {
+        return quoted.split('"').next().unwrap_or("");
+    }
+    match rest.char_indices().nth(5) {
+        Some((i, _)) => &rest[..i],
+        None => rest,
+    }
+}
+
+fn is_symbol(name: &str) -> bool {
+    name.len() <= 10
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+        && name.chars().any(|c| c.is_ascii_alphabetic())
+        && ['H', 'B', 'F']
+            .iter()
+            .all(|s| local_digit(name, *s).is_none())
+}
+
+/// the digit of a local symbol such as `2H`, `2B` or `2F`.
+fn local_digit(name: &str, suffix: char) -> Option<usize> {
+    let mut chars = name.chars();
+    let digit = chars.next()?.to_digit(10)?;
+    (chars.next()? == suffix && chars.next().is_none()).then_some(digit as usize)
+}
+
+#[derive(Default)]
+struct Assembler {
+    symbols: HashMap<String, i64>,
+    // definitions of 0H..9H as (line index, value).
+    locals: [Vec<(usize, i64)>; 10],
+    // line index and location of `END`.
+    end: (usize, i64),
+    location: i64,
+    line: usize,
+    // literals and undefined symbols, placed after the program.
+    pool: Vec<ListingLine>,
+    undefined: HashMap<String, i64>,
+    number: usize,
+    byte_size: ByteSize,
+}
+
+impl Assembler {
+    /// keep a value within the sign and five bytes of a word.
+    fn wrap(&self, value: i128) -> i64 {
+        self.byte_size.wrap(value).0.get_value()
+    }
+
+    fn define_symbols(&mut self, lines: &[Line]) -> Result<(), Box<dyn Error>> {
+        for (index, line) in lines.iter().enumerate() {
+            self.line = index;
+            self.define_line(line)
+                .map_err(|e| format!("line {}: {}", line.number, e))?;
+            if line.op == "END" {
+                self.end = (index, self.location);
+                return Ok(());
+            }
+        }
+        Err("missing END".into())
+    }
+
+    fn define_line(&mut self, line: &Line) -> Result<(), Box<dyn Error>> {
+        match line.op {
+            "EQU" => {
+                let value = self.w_value(line.operand)?.get_value();
+                self.define(line.label, value)
+            }
+            "ORIG" => {
+                self.define(line.label, self.location)?;
+                self.location = self.w_value(line.operand)?.get_value();
+                Ok(())
+            }
+            "END" => self.define(line.label, self.location),
+            "" => Ok(()),
+            _ => {
+                self.define(line.label, self.location)?;
+                self.location += 1;
+                Ok(())
+            }
+        }
+    }
+
+    fn define(&mut self, label: &str, value: i64) -> Result<(), Box<dyn Error>> {
+        if label.is_empty() {
+            return Ok(());
+        }
+        if let Some(digit) = local_digit(label, 'H') {
+            self.locals[digit].push((self.line, value));
+            return Ok(());
+        }
+        if !is_symbol(label) {
+            return Err(format!("invalid symbol `{}`", label).into());
+        }
+        if self.symbols.insert(label.to_string(), value).is_some() {
+            return Err(format!("symbol `{}` defined twice", label).into());
+        }
+        Ok(())
+    }
+
+    fn emit(mut self, lines: &[Line]) -> Result<Assembly, Box<dyn Error>> {
+        let (end_index, end_location) = self.end;
+        let mut computer = MIXComputer::with_byte_size(self.byte_size);
+        let mut start = 0;
+        let mut listing = Vec::new();
+        self.location = 0;
+
+        for (index, line) in lines[..=end_index].iter().enumerate() {
+            self.line = index;
+            self.number = line.number;
+            let mut listed = ListingLine {
+                number: line.number,
+                location: None,
+                word: None,
+                source: line.text.to_string(),
+            };
+            if !line.op.is_empty() {
+                let word = self
+                    .emit_line(line, end_location)
+                    .map_err(|e| format!("line {}: {}", line.number, e))?;
+                match line.op {
+                    "END" => start = word.get_value(),
+                    "ORIG" => self.location = word.get_value(),
+                    "EQU" => (),
+                    _ => {
+                        listed.location = Some(
+                            store(&mut computer, self.location, word)
+                                .map_err(|e| format!("line {}: {}", line.number, e))?,
+                        );
+                        listed.word = Some(word);
+                        self.location += 1;
+                    }
+                }
+            }
+            listing.push(listed);
+        }
+
+        for (offset, mut listed) in self.pool.into_iter().enumerate() {
+            let word = listed.word.unwrap_or(MIXWord(0));
+            listed.location = Some(store(&mut computer, end_location + offset as i64, word)?);
+            listing.push(listed);
+        }
+        let start = usize::try_from(start)
+            .ok()
+            .filter(|s| *s <= MEMORY_MAX)
+            .ok_or("start address out of range")?;
+
+        Ok(Assembly {
+            computer,
+            start,
+            listing: Listing {
+                lines: listing,
+                symbols: self.symbols.into_iter().collect(),
+                byte_size: self.byte_size,
+            },
+        })
+    }
+
+    fn emit_line(&mut self, line: &Line, end_location: i64) -> Result<MIXWord, Box<dyn Error>> {
+        match line.op {
+            "EQU" | "ORIG" | "END" | "CON" => self.w_value(line.operand),
+            "ALF" => alf(line.operand)?.convert(ByteSize::Binary, self.byte_size),
+            op => self.instruction(op, line.operand, end_location),
+        }
+    }
+
+    fn instruction(
+        &mut self,
+        op: &str,
+        operand: &str,
+        end_location: i64,
+    ) -> Result<MIXWord, Box<dyn Error>> {
+        let (c, default_f) = operation(op).ok_or_else(|| format!("unknown operation `{}`", op))?;
+
+        let split = if let Some(literal) = operand.strip_prefix('=') {
+            literal
+                .find('=')
+                .map(|p| p + 2)
+                .ok_or("unterminated literal")?
+        } else {
+            operand.find([',', '(']).unwrap_or(operand.len())
+        };
+        let (address, rest) = operand.split_at(split);
+        let (index, field) = rest.split_at(rest.find('(').unwrap_or(rest.len()));
+
+        let address = self.a_part(address, end_location)?;
+        let index = match index {
+            "" => 0,
+            _ => self.evaluate(index.strip_prefix(',').ok_or("invalid index part")?)?,
+        };
+        let field = match field {
+            "" => default_f as i64,
+            _ => self.evaluate(
+                field
+                    .strip_prefix('(')
+                    .and_then(|f| f.strip_suffix(')'))
+                    .ok_or("invalid field part")?,
+            )?,
+        };
+
+        let b = self.byte_size.base() as i64;
+        if address.abs() >= b * b {
+            return Err(format!("address {} does not fit in two bytes", address).into());
+        }
+        if !(0..=6).contains(&index) {
+            return Err(format!("index {} out of range", index).into());
+        }
+        if !(0..b).contains(&field) {
+            return Err(format!("field {} out of range", field).into());
+        }
+
+        let a = address.unsigned_abs() as u32;
+        Ok(MIXWord::from_bytes(
+            [
+                (address < 0) as u32,
+                a / b as u32,
+                a % b as u32,
+                index as u32,
+                field as u32,
+                c,
+            ],
+            self.byte_size,
+        ))
+    }
+
+    fn a_part(&mut self, address: &str, end_location: i64) -> Result<i64, Box<dyn Error>> {
+        if address.is_empty() {
+            return Ok(0);
+        }
+        if let Some(literal) = address.strip_prefix('=').and_then(|a| a.strip_suffix('=')) {
+            let word = self.w_value(literal)?;
+            return Ok(self.pool(word, address.to_string(), end_location));
+        }
+        if is_symbol(address) && !self.symbols.contains_key(address) {
+            // an undefined symbol becomes a `CON 0` after the program.
+            if let Some(location) = self.undefined.get(address) {
+                return Ok(*location);
+            }
+            let location = self.pool(MIXWord(0), format!("{:8}CON  0", address), end_location);
+            self.undefined.insert(address.to_string(), location);
+            return Ok(location);
+        }
+        self.evaluate(address)
+    }
+
+    /// place a word after the program and return its location.
+    fn pool(&mut self, word: MIXWord, source: String, end_location: i64) -> i64 {
+        self.pool.push(ListingLine {
+            number: self.number,
+            location: None,
+            word: Some(word),
+            source,
+        });
+        end_location + self.pool.len() as i64 - 1
+    }
+
+    fn w_value(&self, operand: &str) -> Result<MIXWord, Box<dyn Error>> {
+        let mut word = MIXWord(0);
+        if operand.is_empty() {
+            return Ok(word);
+        }
+        for part in operand.split(',') {
+            let (expression, field) = match part.split_once('(') {
+                Some((e, f)) => (
+                    e,
+                    self.evaluate(f.strip_suffix(')').ok_or("invalid field part")?)?,
+                ),
+                None => (part, 5),
+            };
+            let (l, r) = (field / 8, field % 8);
+            if l > r || r > 5 {
+                return Err(format!("invalid field ({}:{})", l, r).into());
+            }
+            word.set_range_for(
+                l as u32,
+                r as u32,
+                self.byte_size.wrap(self.evaluate(expression)? as i128).0,
+                self.byte_size,
+            );
+        }
+        Ok(word)
+    }
+
+    /// evaluate an expression strictly from left to right, as MIXAL does.
+    fn evaluate(&self, expression: &str) -> Result<i64, Box<dyn Error>> {
+        let (sign, rest) = match expression.as_bytes().first() {
+            Some(b'+') => (1, &expression[1..]),
+            Some(b'-') => (-1, &expression[1..]),
+            _ => (1, expression),
+        };
+        let (value, mut rest) = self.atom(rest)?;
+        let mut value = sign * value;
+
+        while let Some(c) = rest.chars().next() {
+            let op = if rest.starts_with("//") {
+                "//"
+            } else {
+                &rest[..c.len_utf8()]
+            };
+            let (atom, next) = self.atom(&rest[op.len()..])?;
+            let (lhs, rhs) = (value as i128, atom as i128);
+            value = self.wrap(match op {
+                "+" => lhs + rhs,
+                "-" => lhs - rhs,
+                "*" => lhs * rhs,
+                "/" if rhs != 0 => lhs / rhs,
+                "//" if rhs != 0 => lhs * self.byte_size.word_size() as i128 / rhs,
+                "/" | "//" => return Err("division by zero".into()),
+                ":" => 8 * lhs + rhs,
+                _ => return Err(format!("unknown operator `{}`", op).into()),
+            });
+            rest = next;
+        }
+        Ok(value)
+    }
+
+    fn atom<'a>(&self, text: &'a str) -> Result<(i64, &'a str), Box<dyn Error>> {
+        if let Some(rest) = text.strip_prefix('*') {
+            return Ok((self.location, rest));
+        }
+        let len = text
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(text.len());
+        let (name, rest) = text.split_at(len);
+
+        if name.is_empty() {
+            return Err(format!("expected a number or symbol at `{}`", text).into());
+        }
+        if name.chars().all(|c| c.is_ascii_digit()) {
+            return Ok((self.wrap(name.parse()?), rest));
+        }
+        let value = if let Some(digit) = local_digit(name, 'B') {
+            self.locals[digit]
+                .iter()
+                .rev()
+                .find(|(line, _)| *line < self.line)
+                .map(|(_, v)| *v)
+        } else if let Some(digit) = local_digit(name, 'F') {
+            self.locals[digit]
+                .iter()
+                .find(|(line, _)| *line > self.line)
+                .map(|(_, v)| *v)
+        } else {
+            self.symbols.get(name).copied()
+        };
+        let value = value.ok_or_else(|| format!("undefined symbol `{}`", name))?;
+        Ok((value, rest))
+    }
+}
+
+fn alf(text: &str) -> Result<MIXWord, Box<dyn Error>> {
+    match to_words(&format!("{:5}", text), Unmappable::Error)?[..] {
+        [word] => Ok(word),
+        _ => Err("ALF takes at most five characters".into()),
+    }
+}
+
+fn store(
+    computer: &mut MIXComputer,
+    location: i64,
+    word: MIXWord,
+) -> Result<usize, Box<dyn Error>> {
+    let location = usize::try_from(location)
+        .ok()
+        .filter(|l| *l <= MEMORY_MAX)
+        .ok_or_else(|| format!("location {} out of range", location))?;
+    computer.memory[location] = word;
+    Ok(location)
+}
@@ -0,0 +1,148 @@
+use crate::error::MixError;
+use crate::expression::Expression;
+use crate::mixcpu::MIXCPU;
+use std::fmt::Display;
+
+/// ### Breakpoint
+/// stops a run before the instruction at its location, when its condition holds.
+/// A condition that cannot be evaluated, such as `mem[rI1]` with rI1 negative, stops too.
+/// ```rust
+/// use mixe::breakpoint::Breakpoint;
+/// use mixe::mixcpu::RunOutcome;
+/// use mixe::{MIXComputer, MIXCPU};
+/// let mut computer = MIXCPU::from(MIXComputer::new());
+/// computer.computer.memory[0] = (0, 0, 1, 0, 0, 49).into(); // INC1 1
+/// computer.computer.memory[1] = (0, 0, 0, 0, 0, 39).into(); // JMP 0
+/// let breakpoint = Breakpoint::when("rI1 == 40".parse().unwrap());
+/// computer.breakpoints.insert(0, breakpoint);
+/// assert_eq!(computer.run(), RunOutcome::Breakpoint(0));
+/// assert_eq!(computer.computer.register.i(1).unwrap().get_value(), 40);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub condition: Option<Expression>,
+}
+
+impl Breakpoint {
+    /// stop every time.
+    pub fn new() -> Self {
+        Breakpoint { condition: None }
+    }
+
+    pub fn when(condition: Expression) -> Self {
+        Breakpoint {
+            condition: Some(condition),
+        }
+    }
+
+    pub fn holds(&self, cpu: &MIXCPU) -> bool {
+        match &self.condition {
+            Some(condition) => condition.holds(cpu).unwrap_or(true),
+            None => true,
+        }
+    }
+}
+
+impl Display for Breakpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.condition {
+            Some(condition) => write!(f, "if {}", condition),
+            None => Ok(()),
+        }
+    }
+}
+
+/// ### Tracepoint
+/// writes a message to [`MIXCPU::trace_output`] before the instruction at its location,
+/// when its condition holds, and lets the run go on. Expressions in braces are replaced
+/// by their values.
+/// ```rust
+/// use mixe::breakpoint::Tracepoint;
+/// use mixe::unit::SharedBuffer;
+/// use mixe::{MIXComputer, MIXCPU};
+/// let mut computer = MIXCPU::from(MIXComputer::new());
+/// let output = SharedBuffer::default();
+/// computer.trace_output = Box::new(output.clone());
+/// computer.computer.memory[0] = (0, 0, 1, 0, 0, 49).into(); // INC1 1
+/// computer.computer.memory[1] = (0, 0, 0, 0, 2, 5).into(); // HLT
+/// let tracepoint = Tracepoint::new("rI1 is {rI1}, twice that {rI1 * 2}").unwrap();
+/// computer.tracepoints.insert(1, tracepoint);
+/// computer.run();
+/// assert_eq!(output.contents(), "1: rI1 is 1, twice that 2\n");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tracepoint {
+    pub condition: Option<Expression>,
+    message: Vec<Piece>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Value(Expression),
+}
+
+impl Tracepoint {
+    pub fn new(message: &str) -> Result<Self, MixError> {
+        let mut pieces = Vec::new();
+        let mut rest = message;
+        while let Some(open) = rest.find('{') {
+            let close = rest[open..]
+                .find('}')
+                .ok_or_else(|| MixError::Parse(format!("unclosed {{ in {}", message)))?;
+            pieces.push(Piece::Text(rest[..open].to_string()));
+            pieces.push(Piece::Value(rest[open + 1..open + close].parse()?));
+            rest = &rest[open + close + 1..];
+        }
+        pieces.push(Piece::Text(rest.to_string()));
+        Ok(Tracepoint {
+            condition: None,
+            message: pieces,
+        })
+    }
+
+    /// log only when `condition` holds.
+    pub fn when(mut self, condition: Expression) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    /// the line to log at the current location, if the condition holds.
+    pub fn message(&self, cpu: &MIXCPU) -> Option<String> {
+        if let Some(condition) = &self.condition {
+            match condition.holds(cpu) {
+                Ok(false) => return None,
+                Ok(true) => {}
+                Err(error) => return Some(format!("{}: {}", cpu.location, error)),
+            }
+        }
+        let mut line = format!("{}: ", cpu.location);
+        for piece in &self.message {
+            match piece {
+                Piece::Text(text) => line.push_str(text),
+                Piece::Value(value) => match value.evaluate(cpu) {
+                    Ok(value) => line.push_str(&value.to_string()),
+                    Err(error) => line.push_str(&format!("<{}>", error)),
+                },
+            }
+        }
+        Some(line)
+    }
+}
+
+impl Display for Tracepoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"")?;
+        for piece in &self.message {
+            match piece {
+                Piece::Text(text) => write!(f, "{}", text)?,
+                Piece::Value(value) => write!(f, "{{{}}}", value)?,
+            }
+        }
+        write!(f, "\"")?;
+        match &self.condition {
+            Some(condition) => write!(f, " if {}", condition),
+            None => Ok(()),
+        }
+    }
+}
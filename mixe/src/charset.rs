@@ -0,0 +1,125 @@
+use crate::mixword::MIXWord;
+use std::error::Error;
+
+/// Knuth's MIX character codes: `CHARS[code]` is the character with that code.
+pub const CHARS: [char; 56] = [
+    ' ', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'Δ', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q',
+    'R', 'Σ', 'Π', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '0', '1', '2', '3', '4', '5', '6', '7',
+    '8', '9', '.', ',', '(', ')', '+', '-', '*', '/', '=', '$', '<', '>', '@', ';', ':', '\'',
+];
+
+/// Characters per word.
+pub const WORD_CHARS: usize = 5;
+
+/// What to do with a character that has no MIX code, or a byte that is no character.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Unmappable {
+    Error,
+    /// use this character instead; when encoding it must itself be a MIX character.
+    Replace(char),
+    Skip,
+}
+
+/// ### Code of a character
+/// ```rust
+/// use mixe::charset::char_to_code;
+/// assert_eq!(char_to_code('A'), Some(1));
+/// assert_eq!(char_to_code('Σ'), Some(20));
+/// assert_eq!(char_to_code('0'), Some(30));
+/// assert_eq!(char_to_code('a'), None);
+/// ```
+pub fn char_to_code(c: char) -> Option<u8> {
+    CHARS.iter().position(|m| *m == c).map(|code| code as u8)
+}
+
+pub fn code_to_char(code: u8) -> Option<char> {
+    CHARS.get(code as usize).copied()
+}
+
+/// ### Encode
+/// convert text into MIX character codes.
+/// ```rust
+/// use mixe::charset::{encode, Unmappable};
+/// assert_eq!(encode("A1", Unmappable::Error).unwrap(), vec![1, 31]);
+/// assert_eq!(encode("a1", Unmappable::Replace(' ')).unwrap(), vec![0, 31]);
+/// assert_eq!(encode("a1", Unmappable::Skip).unwrap(), vec![31]);
+/// assert!(encode("a1", Unmappable::Error).is_err());
+/// ```
+pub fn encode(text: &str, policy: Unmappable) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut codes = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match (char_to_code(c), policy) {
+            (Some(code), _) => codes.push(code),
+            (None, Unmappable::Replace(r)) => codes.push(
+                char_to_code(r)
+                    .ok_or_else(|| format!("replacement `{}` is not a MIX character", r))?,
+            ),
+            (None, Unmappable::Skip) => (),
+            (None, Unmappable::Error) => {
+                return Err(format!("character `{}` is not in the MIX character set", c).into())
+            }
+        }
+    }
+    Ok(codes)
+}
+
+/// ### Decode
+/// convert MIX character codes into text.
+/// ```rust
+/// use mixe::charset::{decode, Unmappable};
+/// assert_eq!(decode(&[8, 9, 0, 63], Unmappable::Replace('?')).unwrap(), "HI ?");
+/// ```
+pub fn decode(codes: &[u8], policy: Unmappable) -> Result<String, Box<dyn Error>> {
+    let mut text = String::with_capacity(codes.len());
+    for code in codes {
+        match (code_to_char(*code), policy) {
+            (Some(c), _) => text.push(c),
+            (None, Unmappable::Replace(r)) => text.push(r),
+            (None, Unmappable::Skip) => (),
+            (None, Unmappable::Error) => {
+                return Err(format!("byte {} is not a MIX character", code).into())
+            }
+        }
+    }
+    Ok(text)
+}
+
+/// ### Text to words
+/// pack text five characters to a word, padding the last word with blanks.
+/// ```rust
+/// use mixe::charset::{to_words, Unmappable};
+/// use mixe::MIXWord;
+/// let words = to_words("HELLO WORLD", Unmappable::Error).unwrap();
+/// assert_eq!(words.len(), 3);
+/// assert_eq!(words[2], MIXWord::from((0, 4, 0, 0, 0, 0)));
+/// ```
+pub fn to_words(text: &str, policy: Unmappable) -> Result<Vec<MIXWord>, Box<dyn Error>> {
+    Ok(encode(text, policy)?
+        .chunks(WORD_CHARS)
+        .map(|chunk| {
+            let mut bytes = [0u32; 6];
+            for (i, code) in chunk.iter().enumerate() {
+                bytes[i + 1] = *code as u32;
+            }
+            bytes.into()
+        })
+        .collect())
+}
+
+/// ### Words to text
+/// unpack five characters from each word; signs are ignored.
+/// ```rust
+/// use mixe::charset::{from_words, Unmappable};
+/// let words = [(1, 8, 5, 13, 13, 16).into(), (0, 0, 0, 0, 0, 0).into()];
+/// assert_eq!(from_words(&words, Unmappable::Error).unwrap(), "HELLO     ");
+/// ```
+pub fn from_words(words: &[MIXWord], policy: Unmappable) -> Result<String, Box<dyn Error>> {
+    let codes: Vec<u8> = words
+        .iter()
+        .flat_map(|word| {
+            let bytes: [u32; 6] = (*word).into();
+            bytes.into_iter().skip(1).map(|b| b as u8)
+        })
+        .collect();
+    decode(&codes, policy)
+}
@@ -1,95 +1,363 @@
+use mixe::assembler::{assemble_for, listing_for};
+use mixe::breakpoint::{Breakpoint, Tracepoint};
+use mixe::disassembler::render_for;
+use mixe::mixcpu::RunOutcome;
+use mixe::unit::{CardReader, Device, Disk, LinePrinter, PaperTape, Tape};
+use mixe::watch::Watchpoint;
+use mixe::{MIXWord, Registers, MIXCPU};
 use std::error::Error;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
-use mixe::{MIXCPU, MIXWord};
 
 pub fn handle_command(command: &str, computer: &mut MIXCPU) -> Result<(), Box<dyn Error>> {
+    let (name, argument) = command.split_once(' ').unwrap_or((command, ""));
 
-    if command.len() >= 7 {
-        match &command[..5] {
-            "PRINT" => {
-                let content = String::from(&command[6..]);
-                if content.contains('-') {
-                    let mid = content.find('-').unwrap();
-                    let left: usize = content[..mid].parse().unwrap();
-                    let right: usize = content[mid+1..].parse().unwrap();
-                    if !mixe::mixcpu::MEMORY_RANGE.contains(&right) {
-                        return Err("Index out of range".into());
-                    }
-                    if !mixe::mixcpu::MEMORY_RANGE.contains(&left) {
-                        return Err("Index out of range".into());
-                    }
-                    for i in &computer.computer.memory[left..=right] {
-                        println!(
-                            "({}) {} {:08x} | {:030b}",
-                            i,
-                            i.get_opposite(),
-                            i.get_unsinged(),
-                            i.get_unsinged()
-                        );
-                    }
-                } else if content.contains('r') {
-                    let regnum: usize = String::from(&command[5..])
-                        .replace('A', "0")
-                        .replace('X', "7")
-                        .replace('J', "8")
-                        .into_boxed_str()
-                        .chars().find(|x| x.is_ascii_digit())
-                        .ok_or("Argument Invalid: range error")?
-                        .to_digit(10)
-                        .ok_or("Argument Invalid: range error")?
-                        as usize;
-                    let i = computer.computer.register[regnum];
+    match name {
+        "PRINT" => {
+            let content = String::from(argument);
+            if content.contains('-') {
+                let mid = content.find('-').unwrap();
+                let left: usize = content[..mid].parse()?;
+                let right: usize = content[mid + 1..].parse()?;
+                if !mixe::mixcpu::MEMORY_RANGE.contains(&right) {
+                    return Err("Index out of range".into());
+                }
+                if !mixe::mixcpu::MEMORY_RANGE.contains(&left) {
+                    return Err("Index out of range".into());
+                }
+                for i in &computer.computer.memory[left..=right] {
                     println!(
-                        "{} {:08x} | {:030b}",
+                        "({}) {} {:08x} | {:030b}",
+                        render_for(*i, computer.computer.byte_size),
                         i.get_opposite(),
                         i.get_unsinged(),
                         i.get_unsinged()
                     );
                 }
-                Ok(())
+            } else if content.contains('r') {
+                let regnum: usize = String::from(argument)
+                    .replace('A', "0")
+                    .replace('X', "7")
+                    .replace('J', "8")
+                    .into_boxed_str()
+                    .chars()
+                    .find(|x| x.is_ascii_digit())
+                    .ok_or("Argument Invalid: range error")?
+                    .to_digit(10)
+                    .ok_or("Argument Invalid: range error")?
+                    as usize;
+                let i = computer.computer.register[regnum];
+                println!(
+                    "{} {:08x} | {:030b}",
+                    i.get_opposite(),
+                    i.get_unsinged(),
+                    i.get_unsinged()
+                );
+            }
+            Ok(())
+        }
+        "START" => {
+            if let Ok(x) = String::from(argument).parse() {
+                computer.location = x
             }
-            "START" => {
-                if let Ok(x) = String::from(&command[6..]).parse() {
-                    computer.location = x
-                }
 
-                println!("start at location {}", computer.location);
+            println!("start at location {}", computer.location);
 
-                computer.start();
-                Ok(())
-            }
-            "STORE" => {
-                let location = String::from(&command[6..]);
-                let data = serde_json::to_string(&computer.computer).unwrap();
-                let path = Path::new(&location);
-                let mut file = File::create(path)?;
-                file.write_all(data.as_bytes())?;
-                Ok(())
-            }
-            "CARRY" => {
-                let location = String::from(&command[6..]);
-                let data = std::fs::read_to_string(location)?;
-                computer.computer = serde_json::from_str(&data)?;
-                Ok(())
-            }
-            "PARSE" => {
-                let location = String::from(&command[6..]);
-                for i in std::fs::read_to_string(location)?.lines() {
-                    let mut foo = i.splitn(2, ' ');
-                    let left = foo.next().ok_or("")?;
-                    let right = foo.next().ok_or("")?;
-                    let left: usize = left.parse()?;
-                    let right_parsed: MIXWord = right.try_into()?;
-                    println!("Set memory {} to {} : {}", left, right, right_parsed);
-                    computer.computer.memory[left] = right_parsed;
+            report(computer.run(), computer);
+            Ok(())
+        }
+        "BREAK" => {
+            if !argument.is_empty() {
+                let (location, condition) = split_condition(argument)?;
+                let breakpoint = match condition {
+                    Some(condition) => Breakpoint::when(condition.parse()?),
+                    None => Breakpoint::new(),
+                };
+                computer.breakpoints.insert(location, breakpoint);
+            }
+            for (location, breakpoint) in computer.breakpoints.iter() {
+                let line = format!("breakpoint at {} {}", location, breakpoint);
+                println!("{}", line.trim_end());
+            }
+            Ok(())
+        }
+        "TRACE" => {
+            if !argument.is_empty() {
+                let (point, message) = argument
+                    .split_once('"')
+                    .ok_or("Usage: TRACE addr [IF condition] \"message\"")?;
+                let (location, condition) = split_condition(point.trim())?;
+                let mut tracepoint = Tracepoint::new(message.strip_suffix('"').unwrap_or(message))?;
+                if let Some(condition) = condition {
+                    tracepoint = tracepoint.when(condition.parse()?);
                 }
-                Ok(())
+                computer.tracepoints.insert(location, tracepoint);
+            }
+            for (location, tracepoint) in computer.tracepoints.iter() {
+                println!("tracepoint at {} {}", location, tracepoint);
+            }
+            Ok(())
+        }
+        "DELETE" => {
+            if argument.is_empty() {
+                computer.breakpoints.clear();
+                computer.tracepoints.clear();
+            } else {
+                let location = argument.parse()?;
+                let breakpoint = computer.breakpoints.remove(&location);
+                let tracepoint = computer.tracepoints.remove(&location);
+                if breakpoint.is_none() && tracepoint.is_none() {
+                    return Err(format!("no breakpoint at {}", argument).into());
+                }
+            }
+            Ok(())
+        }
+        "WATCH" => {
+            if !argument.is_empty() {
+                let (watch, read) = match argument.strip_suffix(" READ") {
+                    Some(watch) => (watch, true),
+                    None => (argument, false),
+                };
+                let watchpoint = Watchpoint::new(watch.trim().parse()?);
+                computer.watchpoints.push(match read {
+                    true => watchpoint.reads(),
+                    false => watchpoint,
+                });
+            }
+            for (n, watchpoint) in computer.watchpoints.iter().enumerate() {
+                println!("watchpoint {}: {}", n, watchpoint);
+            }
+            Ok(())
+        }
+        "UNWATCH" => {
+            if argument.is_empty() {
+                computer.watchpoints.clear();
+            } else {
+                let n: usize = argument.parse()?;
+                if n >= computer.watchpoints.len() {
+                    return Err(format!("no watchpoint {}", n).into());
+                }
+                computer.watchpoints.remove(n);
+            }
+            Ok(())
+        }
+        "STEP" => {
+            let n = if argument.is_empty() {
+                1
+            } else {
+                argument.parse()?
+            };
+            report(computer.run_for(n), computer);
+            Ok(())
+        }
+        "NEXT" => {
+            report(computer.step_over(), computer);
+            Ok(())
+        }
+        "CONT" => {
+            report(computer.run(), computer);
+            Ok(())
+        }
+        "BACK" => {
+            let n = if argument.is_empty() {
+                1
+            } else {
+                argument.parse()?
+            };
+            let mut undone = 0;
+            while undone < n && computer.step_back() {
+                undone += 1;
+            }
+            if undone < n {
+                println!("back to the start of the journal");
+            }
+            print_where(computer);
+            Ok(())
+        }
+        "RCONT" => {
+            let outcome = computer.run_back();
+            if outcome == RunOutcome::StepLimit {
+                println!("back to the start of the journal");
             }
-            _ => computer.run_command(command),
+            report(outcome, computer);
+            Ok(())
         }
-    } else {
-        computer.run_command(command)
+        "JOURNAL" => {
+            if !argument.is_empty() {
+                computer.journal.set_capacity(argument.parse()?);
+            }
+            println!(
+                "{} of at most {} instructions can be undone",
+                computer.journal.len(),
+                computer.journal.capacity()
+            );
+            Ok(())
+        }
+        "WHERE" => {
+            print_where(computer);
+            Ok(())
+        }
+        "INTERRUPTS" => {
+            computer.enable_interrupts();
+            println!("interrupt facility enabled, in normal state");
+            Ok(())
+        }
+        "TIME" => {
+            if argument == "RESET" {
+                computer.reset_time();
+            }
+            println!("{}u", computer.time);
+            Ok(())
+        }
+        "BYTES" => {
+            if !argument.is_empty() {
+                computer.computer.byte_size = argument.parse()?;
+            }
+            println!("{} values in a byte", computer.computer.byte_size.base());
+            Ok(())
+        }
+        "STORE" => {
+            let location = String::from(argument);
+            let data = serde_json::to_string(&computer.computer).unwrap();
+            let path = Path::new(&location);
+            let mut file = File::create(path)?;
+            file.write_all(data.as_bytes())?;
+            Ok(())
+        }
+        "CARRY" => {
+            let location = String::from(argument);
+            let data = std::fs::read_to_string(location)?;
+            computer.computer = serde_json::from_str(&data)?;
+            Ok(())
+        }
+        "PARSE" => {
+            let location = String::from(argument);
+            for i in std::fs::read_to_string(location)?.lines() {
+                let mut parts = i.splitn(2, ' ');
+                let left = parts.next().ok_or("")?;
+                let right = parts.next().ok_or("")?;
+                let left: usize = left.parse()?;
+                let right_parsed: MIXWord = right.try_into()?;
+                println!("Set memory {} to {} : {}", left, right, right_parsed);
+                computer.computer.memory[left] = right_parsed;
+            }
+            Ok(())
+        }
+        "ASSEMBLE" => {
+            let program = assemble_for(
+                &std::fs::read_to_string(argument)?,
+                computer.computer.byte_size,
+            )?;
+            computer.computer.memory = program.computer.memory;
+            computer.location = program.start as i64;
+            println!(
+                "assembled {}, start at location {}",
+                argument, program.start
+            );
+            Ok(())
+        }
+        "DISASM" => {
+            let (left, right) = argument.split_once('-').unwrap_or((argument, argument));
+            let left: usize = left.trim().parse()?;
+            let right: usize = right.trim().parse()?;
+            if !mixe::mixcpu::MEMORY_RANGE.contains(&left)
+                || !mixe::mixcpu::MEMORY_RANGE.contains(&right)
+            {
+                return Err("Index out of range".into());
+            }
+            for location in left..=right {
+                println!(
+                    "{:04}: {}",
+                    location,
+                    render_for(
+                        computer.computer.memory[location],
+                        computer.computer.byte_size
+                    )
+                );
+            }
+            Ok(())
+        }
+        "ATTACH" => {
+            let (unit, path) = argument.split_once(' ').ok_or("Usage: ATTACH unit file")?;
+            let unit: usize = unit.parse()?;
+            let device: Box<dyn Device> = match unit {
+                0..=7 => Box::new(Tape::open(path)?),
+                8..=15 => Box::new(Disk::open(path)?),
+                16 => Box::new(CardReader::open(path)?),
+                18 => Box::new(LinePrinter::create(path)?),
+                20 => Box::new(PaperTape::open(path)?),
+                _ => return Err(format!("unit {} cannot be attached to a file", unit).into()),
+            };
+            computer
+                .computer
+                .units
+                .get_mut(unit)
+                .ok_or("Unit invalid.")?
+                .attach(device);
+            Ok(())
+        }
+        "LIST" => {
+            let listing = listing_for(
+                &std::fs::read_to_string(argument)?,
+                computer.computer.byte_size,
+            )?;
+            print!("{}", listing);
+            Ok(())
+        }
+        _ => Ok(computer.run_command(command)?),
+    }
+}
+
+/// a location in memory, and the condition after `IF` if there is one.
+fn split_condition(argument: &str) -> Result<(i64, Option<&str>), Box<dyn Error>> {
+    let (location, condition) = match argument.split_once(" IF ") {
+        Some((location, condition)) => (location, Some(condition)),
+        None => (argument, None),
+    };
+    let location: usize = location.trim().parse()?;
+    if !mixe::mixcpu::MEMORY_RANGE.contains(&location) {
+        return Err("Index out of range".into());
     }
-}
\ No newline at end of file
+    Ok((location as i64, condition))
+}
+
+/// print why a run stopped and, unless it halted, where.
+fn report(outcome: RunOutcome, computer: &MIXCPU) {
+    match outcome {
+        RunOutcome::Halted => {
+            println!("halted");
+            return;
+        }
+        RunOutcome::Fault { error, .. } => println!("{}", error),
+        RunOutcome::StepLimit => {}
+        RunOutcome::Breakpoint(location) => println!("breakpoint at {}", location),
+        RunOutcome::Watchpoint(hits) => {
+            for hit in hits {
+                println!("{}", hit);
+            }
+        }
+    }
+    print_where(computer);
+}
+
+/// print the current location with its instruction, then the registers.
+fn print_where(computer: &MIXCPU) {
+    let instruction = computer.fetch().map_or_else(
+        |error| error.fault().to_string(),
+        |word| render_for(word, computer.computer.byte_size),
+    );
+    println!("{:04}: {}", computer.location, instruction);
+    let registers: Vec<String> = computer
+        .computer
+        .register
+        .iter()
+        .enumerate()
+        .map(|(r, word)| format!("{} {}", Registers::name(r), word.get_value()))
+        .collect();
+    println!("{}", registers.join(" "));
+    println!(
+        "overflow {}, comparison {:?}",
+        computer.computer.overflow, computer.computer.comp
+    );
+}
@@ -1,271 +1,170 @@
-use crate::MIXWord;
-use std::error::Error;
-use std::fmt::Display;
+use crate::disassembler::{default_field, mnemonic};
+use crate::mixword::ByteSize;
+use crate::{MIXWord, MixError};
+use std::collections::HashMap;
+use std::sync::OnceLock;
 
 type Instruction = MIXWord;
 
-impl Display for Instruction {
-    fn fmt(&self, f1: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let op = self.get_op();
-        let f = self.get_f();
-        let i = self.get_i();
-        let aa = self.get_aa();
-        let opposite = self.get_opposite();
-        let reg_char = |x: u32| -> String {
-            x.to_string()
-                .replace('0', "A")
-                .replace('7', "X")
-                .replace('8', "J")
-                .replace('9', "Z")
-        };
-
-        let mut default_f = 5;
-
-        let op_str = match op {
-            0 => "NOP".to_string(),
-            1 => "ADD".to_string(),
-            2 => "SUB".to_string(),
-            3 => "MUL".to_string(),
-            4 => "DIV".to_string(),
-            5 => match f {
-                0 => "NUM",
-                1 => "CHAR",
-                2 => "HLT",
-                _ => panic!("Error: parse failed."),
-            }
-            .to_string(),
-            6 => match f {
-                0 => "SLA",
-                1 => "SRA",
-                2 => "SLAX",
-                3 => "SRAX",
-                4 => "SLC",
-                5 => "SRC",
-                _ => panic!("Error: parse failed."),
-            }
-            .to_string(),
-            7 => {
-                default_f = 1;
-                "MOVE"
-            }
-            .to_string(),
-            8..=15 => {
-                format!("LD{}", reg_char(op - 8))
-            }
-            16..=23 => {
-                format!("LD{}N", reg_char(op - 16))
-            }
-            24..=33 => {
-                if op == 32 {
-                    default_f = 2;
-                }
-                format!("ST{}", reg_char(op - 24))
-            }
-            34 => "JBUS".to_string(),
-            35 => "IOC".to_string(),
-            36 => "IN".to_string(),
-            37 => "OUT".to_string(),
-            38 => "JRED".to_string(),
-            39 => "JMP".to_string(),
-            40..=47 => format!("J{}", reg_char(op - 40)),
-            48..=55 => format!(
-                "{}{}",
-                match f {
-                    0 => "INC",
-                    1 => "DEC",
-                    2 => "ENT",
-                    3 => "ENN",
-                    _ => panic!("Error: parse {} failed.", self.0),
-                },
-                reg_char(op - 48)
-            ),
-            56..=63 => format!("CM{}", reg_char(op - 56)),
-            _ => panic!("Error: parse failed."),
-        };
-
-        let mut result = op_str.to_string();
-
-        if opposite == 1 {
-            result.push('-');
-        }
-
-        result.push_str(&format!(" {}", aa));
-
-        if i != 0 {
-            result.push_str(&format!(",{}", i));
-        }
-
-        if f != default_f {
-            result.push_str(&format!("({})", f));
-        }
-
-        write!(f1, "{}", result)
-    }
-}
-
 impl TryFrom<&str> for Instruction {
-    type Error = Box<dyn Error>;
+    type Error = MixError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         parse(value)
     }
 }
 
-pub fn parse(command: &str) -> Result<MIXWord, Box<dyn Error>> {
-    let mut op_rest = command.splitn(2, ' ');
-    let op = op_rest.next().ok_or("Invalid Argument")?;
-    let rest = op_rest.next().ok_or("Invalid Argument")?;
-    let mut operation = MIXWord::from(0u32);
-    // let default_f = if op != "STJ" { 5 } else { 2 };
-    let default_f = match op {
-        "STJ" => 2,
-        "MOVE" => 1,
-        _ => 5,
-    };
+fn invalid(reason: &str) -> MixError {
+    MixError::Parse(reason.to_string())
+}
 
-    parse_f(&mut operation, rest, default_f)?;
-    parse_i(&mut operation, rest)?;
-    parse_aa(&mut operation, rest)?;
-    parse_op(&mut operation, op)?;
+pub fn parse(command: &str) -> Result<MIXWord, MixError> {
+    parse_for(command, ByteSize::Binary)
+}
+
+/// ### Parse for
+/// read an instruction such as `LDA 2000,2(1:3)` into a word with bytes of the given size.
+/// ```rust
+/// use mixe::command_parser::parse_for;
+/// use mixe::mixword::ByteSize;
+/// let word = parse_for("MOVE 5000(70)", ByteSize::Decimal).unwrap();
+/// assert_eq!(word.bytes(ByteSize::Decimal), [0, 50, 0, 0, 70, 7]);
+/// assert!(parse_for("MOVE 5000(70)", ByteSize::Binary).is_err());
+/// ```
+pub fn parse_for(command: &str, size: ByteSize) -> Result<MIXWord, MixError> {
+    let mut op_rest = command.splitn(2, ' ');
+    let op = op_rest.next().ok_or_else(|| invalid("Invalid Argument"))?;
+    let rest = op_rest.next().ok_or_else(|| invalid("Invalid Argument"))?;
+    let default_f = operation(op).map_or(5, |(_, f)| f);
+    let mut parts = Parts::default();
+
+    parse_f(&mut parts, rest, default_f)?;
+    parse_i(&mut parts, rest)?;
+    parse_aa(&mut parts, rest)?;
+    parse_op(&mut parts, op)?;
+
+    let b = size.base() as u32;
+    if parts.address >= b * b {
+        return Err(invalid("Address out of range."));
+    }
+    if parts.field >= b {
+        return Err(invalid("Field out of range."));
+    }
+    Ok(MIXWord::from_bytes(
+        [
+            parts.sign,
+            parts.address / b,
+            parts.address % b,
+            parts.index,
+            parts.field,
+            parts.op,
+        ],
+        size,
+    ))
+}
 
-    Ok(operation)
+/// the parts `±AA I F C` of an instruction, as they are read.
+#[derive(Default)]
+struct Parts {
+    sign: u32,
+    address: u32,
+    index: u32,
+    field: u32,
+    op: u32,
 }
 
-fn parse_f(operation: &mut MIXWord, rest: &str, default_f: u32) -> Result<(), Box<dyn Error>> {
+fn parse_f(parts: &mut Parts, rest: &str, default_f: u32) -> Result<(), MixError> {
     if rest.contains('(') {
         let left = rest.find('(').unwrap();
-        let right = rest.find(')').ok_or("Argument Invalid.")?;
-        if rest.contains(':') {
-            let mid = rest.find(':').unwrap();
-            let left: u32 = rest[left + 1..mid].parse()?;
-            let right: u32 = rest[mid + 1..right].parse()?;
-            operation.set_f(left * 8 + right);
+        let right = rest.find(')').ok_or_else(|| invalid("Argument Invalid."))?;
+        let field = rest
+            .get(left + 1..right)
+            .ok_or_else(|| invalid("Argument Invalid."))?;
+        if let Some((left, right)) = field.split_once(':') {
+            let left: u32 = left.parse()?;
+            let right: u32 = right.parse()?;
+            parts.field = left
+                .checked_mul(8)
+                .and_then(|field| field.checked_add(right))
+                .ok_or_else(|| invalid("Field out of range."))?;
         } else {
-            let val: u32 = rest[left + 1..right].parse()?;
-            operation.set_f(val);
+            let val: u32 = field.parse()?;
+            parts.field = val;
         }
     } else {
-        operation.set_f(default_f);
+        parts.field = default_f;
     }
     Ok(())
 }
 
-fn parse_i(operation: &mut MIXWord, rest: &str) -> Result<(), Box<dyn Error>> {
+fn parse_i(parts: &mut Parts, rest: &str) -> Result<(), MixError> {
     if rest.contains(',') {
         let pos = rest.find(',').unwrap();
-        let i: u32 = rest[(pos + 1)..(pos + 2)].parse()?;
-        operation.set_i(i);
+        let i: u32 = rest
+            .get((pos + 1)..(pos + 2))
+            .ok_or_else(|| invalid("Argument Invalid."))?
+            .parse()?;
+        parts.index = i;
     }
     Ok(())
 }
 
-fn parse_aa(operation: &mut MIXWord, rest: &str) -> Result<(), Box<dyn Error>> {
+fn parse_aa(parts: &mut Parts, rest: &str) -> Result<(), MixError> {
     let mut address = 0xffffffffu32;
     for i in rest.chars() {
         if i.is_ascii_digit() {
             if address == 0xffffffffu32 {
                 address = i.to_digit(10).unwrap();
             } else {
-                address = address * 10 + i.to_digit(10).unwrap();
+                address = address
+                    .checked_mul(10)
+                    .and_then(|address| address.checked_add(i.to_digit(10).unwrap()))
+                    .ok_or_else(|| invalid("Address out of range."))?;
             }
         } else if address != 0xffffffffu32 {
             break;
         }
     }
-    operation.set_aa(address);
+    parts.address = address;
 
     if rest.contains('-') {
-        operation.set_opposite(1);
+        parts.sign = 1;
     }
     Ok(())
 }
 
-fn parse_op(operation: &mut MIXWord, op: &str) -> Result<(), Box<dyn Error>> {
-    match &op[..2] {
-        "LD" => {
-            let reg = String::from(&op[2..3]).replace('A', "0").replace('X', "7");
-            let num: u32 = reg.parse()?;
-            let is_negative = reg.contains('N');
-            let c = num + 8 + 16 * (is_negative as u32);
-            operation.set_op(c);
-        }
-        "ST" => {
-            let reg = String::from(&op[2..3])
-                .replace('A', "0")
-                .replace('X', "7")
-                .replace('J', "8")
-                .replace('Z', "9");
-            let num: u32 = reg.parse()?;
-            operation.set_op(num + 24);
-        }
-        "AD" => operation.set_op(1),
-        "SU" => operation.set_op(2),
-        "MU" => operation.set_op(3),
-        "DI" => operation.set_op(4),
-        "EN" | "IN" | "DE" => {
-            if op.len() == 2 {
-                // IN Operation
-                operation.set_op(36)
-            } else {
-                let reg = String::from(&op[3..4]).replace('A', "0").replace('X', "7");
-                let reg: u32 = reg.parse()?;
-                operation.set_op(48 + reg);
-            }
-
-            operation.set_f(match &op[..3] {
-                "INC" => 0,
-                "DEC" => 1,
-                "ENT" => 2,
-                "ENN" => 3,
-                _ => return Err("Invalid Command".into()),
-            });
-        }
-        "CM" => {
-            let reg = String::from(&op[3..4]).replace('A', "0").replace('X', "7");
-            let reg: u32 = reg.parse()?;
-            operation.set_op(56 + reg);
-        }
-        "JM" | "JS" | "JO" | "JN" | "JL" | "JG" | "JE" => {
-            operation.set_op(39);
-            operation.set_f(match op {
-                "JMP" => 0,
-                "JSJ" => 1,
-                "JOV" => 2,
-                "JNOV" => 3,
-                "JL" => 4,
-                "JE" => 5,
-                "JG" => 6,
-                "JGE" => 7,
-                "JNE" => 8,
-                "JLE" => 9,
-                _ => return Err("Invalid Command".into()),
-            });
-        }
-        "JA" | "JX" | "J1" | "J2" | "J3" | "J4" | "J5" | "J6" => {
-            let reg = String::from(&op[1..2]).replace('A', "0").replace('X', "7");
-            let reg: u32 = reg.parse()?;
-            operation.set_op(40 + reg);
-        }
-        "SL" | "SR" => operation.set_op(6),
-        "MO" => operation.set_op(7),
-        "NO" => operation.set_op(0),
-        "HL" => {
-            operation.set_op(5);
-            operation.set_f(2);
-        }
-        "JB" => operation.set_op(34),
-        "IO" => operation.set_op(35),
-        "OU" => operation.set_op(37),
-        "JR" => operation.set_op(38),
-        "NU" => {
-            operation.set_op(5);
-            operation.set_f(0);
-        }
-        "CH" => {
-            operation.set_op(5);
-            operation.set_f(1);
-        }
-        _ => return Err("Unknown Operation.".into()),
+fn parse_op(parts: &mut Parts, op: &str) -> Result<(), MixError> {
+    let (c, f) = operation(op).ok_or_else(|| invalid("Unknown Operation."))?;
+    parts.op = c;
+    // F is part of the operation when another F names another one, as `JMP` and `JSJ`.
+    if mnemonic(c, f ^ 1).as_deref() != Some(op) {
+        parts.field = f;
     }
     Ok(())
 }
+
+/// ### Operation
+/// look up the `C` and default `F` of a MIXAL mnemonic, as the inverse of
+/// [`mnemonic`], so the REPL, the assembler and the disassembler share one table.
+/// ```rust
+/// use mixe::command_parser::operation;
+/// assert_eq!(operation("LD2N"), Some((18, 5)));
+/// assert_eq!(operation("JXNZ"), Some((47, 4)));
+/// assert_eq!(operation("LDY"), None);
+/// ```
+pub fn operation(name: &str) -> Option<(u32, u32)> {
+    static OPERATIONS: OnceLock<HashMap<String, (u32, u32)>> = OnceLock::new();
+    let operations = OPERATIONS.get_or_init(|| {
+        let mut operations = HashMap::new();
+        for c in 0..64 {
+            // the default F first, so `LDA` is (8, 5) rather than (8, 0).
+            for f in std::iter::once(default_field(c)).chain(0..64) {
+                if let Some(name) = mnemonic(c, f) {
+                    operations.entry(name).or_insert((c, f));
+                }
+            }
+        }
+        operations
+    });
+    operations.get(name).copied()
+}
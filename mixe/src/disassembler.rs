@@ -0,0 +1,189 @@
+use crate::charset::{from_words, Unmappable};
+use crate::mixword::{ByteSize, MIXWord};
+use std::error::Error;
+use std::fmt::Display;
+
+/// ### Mnemonic
+/// the symbolic name of an operation, or `None` if `C` and `F` name no instruction.
+/// ```rust
+/// use mixe::disassembler::mnemonic;
+/// assert_eq!(mnemonic(18, 5), Some("LD2N".to_string()));
+/// assert_eq!(mnemonic(5, 3), None);
+/// ```
+pub fn mnemonic(c: u32, f: u32) -> Option<String> {
+    let name = match (c, f) {
+        (1, 6) => "FADD".to_string(),
+        (2, 6) => "FSUB".to_string(),
+        (3, 6) => "FMUL".to_string(),
+        (4, 6) => "FDIV".to_string(),
+        (5, 6) => "FLOT".to_string(),
+        (5, 7) => "FIX".to_string(),
+        (56, 6) => "FCMP".to_string(),
+        _ => return integer_mnemonic(c, f),
+    };
+    Some(name)
+}
+
+fn integer_mnemonic(c: u32, f: u32) -> Option<String> {
+    let reg = |r: u32| match r {
+        0 => "A".to_string(),
+        7 => "X".to_string(),
+        r => r.to_string(),
+    };
+    let name = match c {
+        0 => "NOP".to_string(),
+        1 => "ADD".to_string(),
+        2 => "SUB".to_string(),
+        3 => "MUL".to_string(),
+        4 => "DIV".to_string(),
+        5 if f == 9 => "INT".to_string(),
+        5 => ["NUM", "CHAR", "HLT"].get(f as usize)?.to_string(),
+        6 => ["SLA", "SRA", "SLAX", "SRAX", "SLC", "SRC", "SLB", "SRB"]
+            .get(f as usize)?
+            .to_string(),
+        7 => "MOVE".to_string(),
+        8..=15 => format!("LD{}", reg(c - 8)),
+        16..=23 => format!("LD{}N", reg(c - 16)),
+        24..=31 => format!("ST{}", reg(c - 24)),
+        32 => "STJ".to_string(),
+        33 => "STZ".to_string(),
+        34 => "JBUS".to_string(),
+        35 => "IOC".to_string(),
+        36 => "IN".to_string(),
+        37 => "OUT".to_string(),
+        38 => "JRED".to_string(),
+        39 => [
+            "JMP", "JSJ", "JOV", "JNOV", "JL", "JE", "JG", "JGE", "JNE", "JLE",
+        ]
+        .get(f as usize)?
+        .to_string(),
+        40..=47 => format!(
+            "J{}{}",
+            reg(c - 40),
+            ["N", "Z", "P", "NN", "NZ", "NP", "E", "O"].get(f as usize)?
+        ),
+        48..=55 => format!(
+            "{}{}",
+            ["INC", "DEC", "ENT", "ENN"].get(f as usize)?,
+            reg(c - 48)
+        ),
+        56..=63 => format!("CMP{}", reg(c - 56)),
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// the F that operation `c` takes when its mnemonic leaves F free: (0:2) for `STJ`,
+/// (0:5) for the other field operations, 1 for `MOVE` and 0 otherwise.
+pub(crate) fn default_field(c: u32) -> u32 {
+    match c {
+        7 => 1,
+        32 => 2,
+        1..=4 | 8..=33 | 56..=63 => 5,
+        _ => 0,
+    }
+}
+
+/// whether the F part of operation `c` is a field specification (L:R).
+fn has_field(c: u32, f: u32) -> bool {
+    matches!(c, 1..=4 | 8..=33 | 56..=63) && !(f == 6 && matches!(c, 1..=4 | 56))
+}
+
+/// ### Disassemble
+/// render a word as a MIXAL instruction, or fail if it is not a valid one.
+/// ```rust
+/// use mixe::disassembler::disassemble;
+/// use mixe::MIXWord;
+/// let word: MIXWord = (0, 0, 2000, 2, 11, 8).into();
+/// assert_eq!(disassemble(word).unwrap(), "LDA 2000,2(1:3)");
+/// assert!(disassemble((0, 0, 0, 0, 3, 5).into()).is_err());
+/// ```
+pub fn disassemble(word: MIXWord) -> Result<String, Box<dyn Error>> {
+    disassemble_for(word, ByteSize::Binary)
+}
+
+/// ### Disassemble for
+/// [`disassemble`] a word with bytes of the given size.
+/// ```rust
+/// use mixe::disassembler::disassemble_for;
+/// use mixe::mixword::ByteSize;
+/// use mixe::MIXWord;
+/// let word = MIXWord::from_bytes([0, 20, 0, 1, 70, 7], ByteSize::Decimal);
+/// assert_eq!(disassemble_for(word, ByteSize::Decimal).unwrap(), "MOVE 2000,1(70)");
+/// ```
+pub fn disassemble_for(word: MIXWord, size: ByteSize) -> Result<String, Box<dyn Error>> {
+    let [sign, a1, a2, i, f, c] = word.bytes(size);
+    let name = mnemonic(c, f).ok_or_else(|| format!("no instruction with C={} F={}", c, f))?;
+    if i > 6 {
+        return Err(format!("index {} out of range", i).into());
+    }
+
+    let sign = if sign == 1 { "-" } else { "" };
+    let address = a1 * size.base() as u32 + a2;
+    let mut result = format!("{} {}{}", name, sign, address);
+    if i != 0 {
+        result.push_str(&format!(",{}", i));
+    }
+
+    if has_field(c, f) {
+        let (l, r) = (f / 8, f % 8);
+        if l > r || r > 5 {
+            return Err(format!("invalid field ({}:{})", l, r).into());
+        }
+        if f != default_field(c) {
+            result.push_str(&format!("({}:{})", l, r));
+        }
+    } else {
+        match c {
+            7 if f != default_field(c) => result.push_str(&format!("({})", f)),
+            34..=38 if f > 20 => return Err(format!("unit {} out of range", f).into()),
+            34..=38 => result.push_str(&format!("({})", f)),
+            _ => (),
+        }
+    }
+    Ok(result)
+}
+
+/// ### Render
+/// render any word: as an instruction when it is one, otherwise as `ALF` when it
+/// looks like text (positive, first byte not blank, every byte a MIX character),
+/// otherwise as `CON`.
+/// ```rust
+/// use mixe::disassembler::render;
+/// assert_eq!(render((0, 0, 0, 0, 3, 5).into()), "CON 197");
+/// assert_eq!(render((0, 22, 24, 14, 40, 40).into()), "ALF \"SUM..\"");
+/// ```
+pub fn render(word: MIXWord) -> String {
+    render_for(word, ByteSize::Binary)
+}
+
+/// ### Render for
+/// [`render`] a word with bytes of the given size.
+/// ```rust
+/// use mixe::disassembler::render_for;
+/// use mixe::mixword::ByteSize;
+/// use mixe::MIXWord;
+/// let sum = MIXWord::from_bytes([0, 22, 24, 14, 40, 40], ByteSize::Decimal);
+/// assert_eq!(render_for(sum, ByteSize::Decimal), "ALF \"SUM..\"");
+/// ```
+pub fn render_for(word: MIXWord, size: ByteSize) -> String {
+    if let Ok(instruction) = disassemble_for(word, size) {
+        return instruction;
+    }
+    let bytes = word.bytes(size);
+    let text = word
+        .convert(size, ByteSize::Binary)
+        .and_then(|word| from_words(&[word], Unmappable::Error));
+    match text {
+        Ok(text) if bytes[0] == 0 && bytes[1] != 0 => format!("ALF \"{}\"", text),
+        _ if word.get_opposite() == 1 => format!("CON -{}", word.get_unsinged()),
+        _ => format!("CON {}", word.get_unsinged()),
+    }
+}
+
+/// a word shows as [`render`] shows it with binary bytes.
+impl Display for MIXWord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", render(*self))
+    }
+}
@@ -0,0 +1,107 @@
+use crate::mixword::MIXWord;
+use std::error::Error;
+use std::fmt::Display;
+use std::num::ParseIntError;
+
+/// ### Mix error
+/// why the machine could not go on. Errors from executing an instruction come
+/// wrapped in [`MixError::At`], which tells where the fault happened.
+/// ```rust
+/// use mixe::{MIXComputer, MIXCPU, MixError};
+/// let mut computer = MIXCPU::from(MIXComputer::new());
+/// computer.location = 100;
+/// let error = computer.run_command("LDA 4000").unwrap_err();
+/// assert_eq!(error.location(), Some(100));
+/// assert_eq!(error.fault(), &MixError::Address(4000));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MixError {
+    /// an address outside memory, or a negative one outside control state.
+    Address(i64),
+    /// a word whose operation code or field means nothing.
+    InvalidInstruction(MIXWord),
+    DivideByZero,
+    /// a result TAOCP leaves undefined, such as an index register given three bytes.
+    Undefined(String),
+    /// a unit that does not exist, or an error from its device.
+    Device {
+        unit: usize,
+        reason: String,
+    },
+    /// a command that could not be parsed.
+    Parse(String),
+    /// an I/O error outside the units, such as writing trace output.
+    Io(String),
+    /// an error while executing `instruction` at `location`.
+    At {
+        location: i64,
+        instruction: MIXWord,
+        error: Box<MixError>,
+    },
+}
+
+impl MixError {
+    /// the error itself, without where it happened.
+    pub fn fault(&self) -> &MixError {
+        match self {
+            MixError::At { error, .. } => error.fault(),
+            error => error,
+        }
+    }
+
+    /// the location of the faulting instruction, if known.
+    pub fn location(&self) -> Option<i64> {
+        match self {
+            MixError::At { location, .. } => Some(*location),
+            _ => None,
+        }
+    }
+
+    /// the faulting instruction, if known.
+    pub fn instruction(&self) -> Option<MIXWord> {
+        match self {
+            MixError::At { instruction, .. } => Some(*instruction),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn device(unit: usize, reason: impl Display) -> Self {
+        MixError::Device {
+            unit,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl Display for MixError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MixError::Address(address) => write!(f, "address {} out of range", address),
+            MixError::InvalidInstruction(word) => write!(f, "invalid instruction {}", word),
+            MixError::DivideByZero => write!(f, "divide by 0"),
+            MixError::Undefined(reason) => write!(f, "undefined: {}", reason),
+            MixError::Device { unit, reason } => write!(f, "unit {}: {}", unit, reason),
+            MixError::Parse(reason) => write!(f, "{}", reason),
+            MixError::Io(reason) => write!(f, "I/O error: {}", reason),
+            MixError::At {
+                location,
+                instruction,
+                error,
+            } => write!(f, "{} at location {} ({})", error, location, instruction),
+        }
+    }
+}
+
+impl Error for MixError {}
+
+impl From<std::io::Error> for MixError {
+    fn from(error: std::io::Error) -> Self {
+        MixError::Io(error.to_string())
+    }
+}
+
+impl From<ParseIntError> for MixError {
+    fn from(error: ParseIntError) -> Self {
+        MixError::Parse(error.to_string())
+    }
+}
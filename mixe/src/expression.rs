@@ -0,0 +1,360 @@
+use crate::error::MixError;
+use crate::mixcpu::{MEMORY_RANGE, MIXCPU};
+use crate::registers::{A, J, X};
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+/// ### Expression
+/// a condition or value over the state of the machine, as in `rA > 100`,
+/// `mem[2000](1:3) == 5` or `rI1 == 0 && OV`.
+///
+/// Values are numbers; comparisons and `&&`, `||`, `!` give 1 or 0, and any
+/// number but 0 is true. `rA`, `rI1` to `rI6`, `rX` and `rJ` are the registers,
+/// `mem[e]` the word at location `e`, and either may be followed by a field `(L:R)`.
+/// `OV` is the overflow toggle, `CI` the comparison indicator as -1, 0 or 1,
+/// `LOC` the current location and `TIME` the time elapsed.
+/// ```rust
+/// use mixe::expression::Expression;
+/// use mixe::{MIXComputer, MIXCPU, MIXWord};
+/// let mut computer = MIXCPU::from(MIXComputer::new());
+/// computer.computer.memory[2000] = MIXWord::from_value(-(5 << 12));
+/// let expression: Expression = "mem[2000](1:3) == 5 && !OV".parse().unwrap();
+/// assert!(expression.holds(&computer).unwrap());
+/// let expression: Expression = "mem[1000 * 4] + 1".parse().unwrap();
+/// assert!(expression.evaluate(&computer).is_err());
+/// assert!("rA >".parse::<Expression>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expression {
+    source: String,
+    node: Node,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Number(i64),
+    Register(usize, Option<(u32, u32)>),
+    Memory(Box<Node>, Option<(u32, u32)>),
+    Overflow,
+    Comparison,
+    Location,
+    Time,
+    Not(Box<Node>),
+    Negate(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Number(i64),
+    Name(String),
+    Symbol(&'static str),
+}
+
+// longer symbols first, so that `<=` is not read as `<`.
+const SYMBOLS: [&str; 19] = [
+    "&&", "||", "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "%", "!", "(", ")", "[", "]",
+    ":",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, MixError> {
+    let mut tokens = Vec::new();
+    let mut rest = source.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let length = if c.is_ascii_digit() {
+            let length = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            tokens.push(Token::Number(rest[..length].parse()?));
+            length
+        } else if c.is_ascii_alphabetic() {
+            let length = rest
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .unwrap_or(rest.len());
+            tokens.push(Token::Name(rest[..length].to_string()));
+            length
+        } else {
+            let symbol = SYMBOLS
+                .into_iter()
+                .find(|symbol| rest.starts_with(symbol))
+                .ok_or_else(|| MixError::Parse(format!("unexpected {} in {}", c, source)))?;
+            tokens.push(Token::Symbol(symbol));
+            symbol.len()
+        };
+        rest = rest[length..].trim_start();
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self) -> MixError {
+        match self.tokens.get(self.position) {
+            Some(token) => MixError::Parse(format!("unexpected {:?} in {}", token, self.source)),
+            None => MixError::Parse(format!("unexpected end of {}", self.source)),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    /// take `symbol` if it comes next.
+    fn eat(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), MixError> {
+        match self.eat(symbol) {
+            true => Ok(()),
+            false => Err(self.error()),
+        }
+    }
+
+    /// a chain of `next` separated by the operators in `operators`, left to right.
+    fn chain(
+        &mut self,
+        operators: &[(&str, Operator)],
+        next: fn(&mut Self) -> Result<Node, MixError>,
+    ) -> Result<Node, MixError> {
+        let mut node = next(self)?;
+        'chain: loop {
+            for (symbol, operator) in operators {
+                if self.eat(symbol) {
+                    node = Node::Binary(*operator, Box::new(node), Box::new(next(self)?));
+                    continue 'chain;
+                }
+            }
+            return Ok(node);
+        }
+    }
+
+    fn or(&mut self) -> Result<Node, MixError> {
+        self.chain(&[("||", Operator::Or)], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Node, MixError> {
+        self.chain(&[("&&", Operator::And)], Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Node, MixError> {
+        let operators = [
+            ("==", Operator::Equal),
+            ("!=", Operator::NotEqual),
+            ("<=", Operator::LessEqual),
+            (">=", Operator::GreaterEqual),
+            ("<", Operator::Less),
+            (">", Operator::Greater),
+        ];
+        self.chain(&operators, Self::sum)
+    }
+
+    fn sum(&mut self) -> Result<Node, MixError> {
+        let operators = [("+", Operator::Add), ("-", Operator::Subtract)];
+        self.chain(&operators, Self::product)
+    }
+
+    fn product(&mut self) -> Result<Node, MixError> {
+        let operators = [
+            ("*", Operator::Multiply),
+            ("/", Operator::Divide),
+            ("%", Operator::Remainder),
+        ];
+        self.chain(&operators, Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Node, MixError> {
+        if self.eat("!") {
+            Ok(Node::Not(Box::new(self.unary()?)))
+        } else if self.eat("-") {
+            Ok(Node::Negate(Box::new(self.unary()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Node, MixError> {
+        let token = self.peek().cloned().ok_or_else(|| self.error())?;
+        if self.eat("(") {
+            let node = self.or()?;
+            self.expect(")")?;
+            return Ok(node);
+        }
+        let name = match token {
+            Token::Number(n) => {
+                self.position += 1;
+                return Ok(Node::Number(n));
+            }
+            Token::Name(name) => name,
+            Token::Symbol(_) => return Err(self.error()),
+        };
+        self.position += 1;
+        let register = match name.as_str() {
+            "rA" => A,
+            "rX" => X,
+            "rJ" => J,
+            "OV" => return Ok(Node::Overflow),
+            "CI" => return Ok(Node::Comparison),
+            "LOC" => return Ok(Node::Location),
+            "TIME" => return Ok(Node::Time),
+            "mem" => {
+                self.expect("[")?;
+                let address = self.or()?;
+                self.expect("]")?;
+                return Ok(Node::Memory(Box::new(address), self.field()?));
+            }
+            _ => match name.strip_prefix("rI").map(str::parse) {
+                Some(Ok(n @ 1..=6)) => n,
+                _ => {
+                    self.position -= 1;
+                    return Err(self.error());
+                }
+            },
+        };
+        Ok(Node::Register(register, self.field()?))
+    }
+
+    /// a field `(L:R)`, if one comes next.
+    fn field(&mut self) -> Result<Option<(u32, u32)>, MixError> {
+        let starts_field = matches!(
+            self.tokens.get(self.position..self.position + 3),
+            Some([Token::Symbol("("), Token::Number(_), Token::Symbol(":")])
+        );
+        if !starts_field {
+            return Ok(None);
+        }
+        let number = |token: Option<&Token>| match token {
+            Some(Token::Number(n)) if (0..=5).contains(n) => Some(*n as u32),
+            _ => None,
+        };
+        let left = number(self.tokens.get(self.position + 1));
+        let right = number(self.tokens.get(self.position + 3));
+        self.position += 4;
+        self.expect(")")?;
+        match (left, right) {
+            (Some(left), Some(right)) if left <= right => Ok(Some((left, right))),
+            _ => Err(MixError::Parse(format!("invalid field in {}", self.source))),
+        }
+    }
+}
+
+impl Expression {
+    pub fn evaluate(&self, cpu: &MIXCPU) -> Result<i64, MixError> {
+        evaluate(&self.node, cpu)
+    }
+
+    /// whether the expression is not 0.
+    pub fn holds(&self, cpu: &MIXCPU) -> Result<bool, MixError> {
+        Ok(self.evaluate(cpu)? != 0)
+    }
+}
+
+fn evaluate(node: &Node, cpu: &MIXCPU) -> Result<i64, MixError> {
+    let size = cpu.computer.byte_size;
+    let field = |word: crate::MIXWord, field: &Option<(u32, u32)>| match field {
+        Some((left, right)) => word.get_range_for(*left, *right, size).get_value(),
+        None => word.get_value(),
+    };
+    Ok(match node {
+        Node::Number(n) => *n,
+        Node::Register(r, f) => field(cpu.computer.register[*r], f),
+        Node::Memory(address, f) => {
+            let address = evaluate(address, cpu)?;
+            match usize::try_from(address) {
+                Ok(address) if MEMORY_RANGE.contains(&address) => {
+                    field(cpu.computer.memory[address], f)
+                }
+                _ => return Err(MixError::Address(address)),
+            }
+        }
+        Node::Overflow => cpu.computer.overflow as i64,
+        Node::Comparison => match cpu.computer.comp {
+            Ordering::Less => -1,
+            Ordering::Equal => 0,
+            Ordering::Greater => 1,
+        },
+        Node::Location => cpu.location,
+        Node::Time => cpu.time as i64,
+        Node::Not(node) => (evaluate(node, cpu)? == 0) as i64,
+        Node::Negate(node) => evaluate(node, cpu)?.wrapping_neg(),
+        Node::Binary(operator, left, right) => {
+            let left = evaluate(left, cpu)?;
+            // `&&` and `||` do not evaluate their right side when the left decides.
+            match operator {
+                Operator::And if left == 0 => return Ok(0),
+                Operator::Or if left != 0 => return Ok(1),
+                _ => {}
+            }
+            let right = evaluate(right, cpu)?;
+            match operator {
+                Operator::Or | Operator::And => (right != 0) as i64,
+                Operator::Equal => (left == right) as i64,
+                Operator::NotEqual => (left != right) as i64,
+                Operator::Less => (left < right) as i64,
+                Operator::LessEqual => (left <= right) as i64,
+                Operator::Greater => (left > right) as i64,
+                Operator::GreaterEqual => (left >= right) as i64,
+                Operator::Add => left.wrapping_add(right),
+                Operator::Subtract => left.wrapping_sub(right),
+                Operator::Multiply => left.wrapping_mul(right),
+                Operator::Divide | Operator::Remainder if right == 0 => {
+                    return Err(MixError::DivideByZero)
+                }
+                Operator::Divide => left.wrapping_div(right),
+                Operator::Remainder => left.wrapping_rem(right),
+            }
+        }
+    })
+}
+
+impl std::str::FromStr for Expression {
+    type Err = MixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            source: s,
+            tokens: tokenize(s)?,
+            position: 0,
+        };
+        let node = parser.or()?;
+        if parser.position < parser.tokens.len() {
+            return Err(parser.error());
+        }
+        Ok(Expression {
+            source: s.trim().to_string(),
+            node,
+        })
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
@@ -0,0 +1,286 @@
+use crate::error::MixError;
+use crate::mixword::{ByteSize, MIXWord};
+use std::cmp::Ordering;
+
+/// Bytes in the fraction, p.
+const DIGITS: i32 = 4;
+/// Beyond this many bytes of difference in exponents, `FCMP` ignores the smaller operand.
+const FAR: i64 = 12;
+
+/// The result of a floating point operation, and whether the exponent overflowed
+/// or underflowed.
+pub type Rounded = (MIXWord, bool);
+
+/// the byte size b and the exponent excess q = b / 2.
+#[derive(Clone, Copy)]
+struct Format {
+    base: u128,
+    excess: i64,
+}
+
+impl From<ByteSize> for Format {
+    fn from(size: ByteSize) -> Self {
+        Format {
+            base: size.base() as u128,
+            excess: size.base() as i64 / 2,
+        }
+    }
+}
+
+impl Format {
+    fn pow(self, n: i32) -> u128 {
+        self.base.pow(n as u32)
+    }
+
+    fn unpack(self, word: MIXWord) -> (bool, i64, u128) {
+        let magnitude = word.get_unsinged() as u128;
+        (
+            word.get_opposite() == 1,
+            (magnitude / self.pow(DIGITS)) as i64,
+            magnitude % self.pow(DIGITS),
+        )
+    }
+
+    fn pack(self, negative: bool, exponent: i64, fraction: u128) -> MIXWord {
+        let mut word = MIXWord((exponent as u128 * self.pow(DIGITS) + fraction) as u64);
+        word.set_opposite(negative as u32);
+        word
+    }
+
+    /// ### Normalize
+    /// Algorithm N of TAOCP 4.2.1: normalise `±fraction * b^(e - q - scale)` and round it
+    /// to four bytes, ties to even; `sticky` says that the true fraction is a little more.
+    /// An exponent outside 0..b is kept modulo b and reported as an overflow.
+    fn normalize(
+        self,
+        negative: bool,
+        mut e: i64,
+        fraction: u128,
+        mut scale: i32,
+        sticky: bool,
+    ) -> Rounded {
+        if fraction == 0 {
+            return (self.pack(negative, 0, 0), false);
+        }
+        while fraction >= self.pow(scale) {
+            scale += 1;
+            e += 1;
+        }
+        while fraction < self.pow(scale - 1) {
+            scale -= 1;
+            e -= 1;
+        }
+
+        let mut f = if scale <= DIGITS {
+            fraction * self.pow(DIGITS - scale)
+        } else {
+            let unit = self.pow(scale - DIGITS);
+            let (f, rest) = (fraction / unit, fraction % unit);
+            match (2 * rest).cmp(&unit) {
+                Ordering::Greater => f + 1,
+                Ordering::Equal if sticky || f % 2 == 1 => f + 1,
+                _ => f,
+            }
+        };
+        if f == self.pow(DIGITS) {
+            f = self.pow(DIGITS - 1);
+            e += 1;
+        }
+        let base = self.base as i64;
+        let overflow = !(0..base).contains(&e);
+        (self.pack(negative, e.rem_euclid(base), f), overflow)
+    }
+}
+
+/// the exact `u - v` as `(value, e)` meaning `value * b^(e - q - p)`.
+fn difference(u: MIXWord, v: MIXWord, format: Format) -> (i128, i64) {
+    let signed = |word: MIXWord| {
+        let (negative, e, f) = format.unpack(word);
+        (e, if negative { -(f as i128) } else { f as i128 })
+    };
+    let (eu, fu) = signed(u);
+    let (ev, fv) = signed(v);
+    let fv = -fv;
+    if fu == 0 {
+        return (fv, ev);
+    }
+    if fv == 0 {
+        return (fu, eu);
+    }
+    let ((e1, f1), (e2, f2)) = if eu >= ev {
+        ((eu, fu), (ev, fv))
+    } else {
+        ((ev, fv), (eu, fu))
+    };
+    if e1 - e2 > FAR {
+        (f1, e1)
+    } else {
+        (f1 * format.pow((e1 - e2) as i32) as i128 + f2, e2)
+    }
+}
+
+/// ### Floating add
+/// `FADD`, Algorithm A of TAOCP 4.2.1.
+/// ```rust
+/// use mixe::float::{fadd, flot};
+/// use mixe::mixword::ByteSize::Binary;
+/// use mixe::MIXWord;
+/// let (one, _) = flot(MIXWord::from_value(1), Binary);
+/// let (two, _) = flot(MIXWord::from_value(2), Binary);
+/// assert_eq!(fadd(one, one, Binary), (two, false));
+/// ```
+pub fn fadd(u: MIXWord, v: MIXWord, size: ByteSize) -> Rounded {
+    let format = Format::from(size);
+    let (su, eu, fu) = format.unpack(u);
+    let (sv, ev, fv) = format.unpack(v);
+    let ((s1, e1, f1), (s2, e2, f2)) = if eu >= ev || fv == 0 {
+        ((su, eu, fu), (sv, ev, fv))
+    } else {
+        ((sv, ev, fv), (su, eu, fu))
+    };
+    if f2 == 0 || e1 - e2 >= DIGITS as i64 + 2 {
+        return format.normalize(s1, e1, f1, DIGITS, false);
+    }
+    let signed = |negative: bool, f: u128| if negative { -(f as i128) } else { f as i128 };
+    let sum = signed(s1, f1 * format.pow((e1 - e2) as i32)) + signed(s2, f2);
+    format.normalize(sum < 0, e2, sum.unsigned_abs(), DIGITS, false)
+}
+
+/// `FSUB`: `u - v`.
+pub fn fsub(u: MIXWord, v: MIXWord, size: ByteSize) -> Rounded {
+    let mut v = v;
+    v.set_opposite(1 - v.get_opposite());
+    fadd(u, v, size)
+}
+
+/// ### Floating multiply
+/// `FMUL`, Algorithm M of TAOCP 4.2.1.
+/// ```rust
+/// use mixe::float::{flot, fmul};
+/// use mixe::mixword::ByteSize::Decimal;
+/// use mixe::MIXWord;
+/// let (three, _) = flot(MIXWord::from_value(3), Decimal);
+/// let (minus_three, _) = flot(MIXWord::from_value(-3), Decimal);
+/// let (minus_nine, _) = flot(MIXWord::from_value(-9), Decimal);
+/// assert_eq!(fmul(three, minus_three, Decimal), (minus_nine, false));
+/// ```
+pub fn fmul(u: MIXWord, v: MIXWord, size: ByteSize) -> Rounded {
+    let format = Format::from(size);
+    let (su, eu, fu) = format.unpack(u);
+    let (sv, ev, fv) = format.unpack(v);
+    format.normalize(
+        su != sv,
+        eu + ev - format.excess,
+        fu * fv,
+        2 * DIGITS,
+        false,
+    )
+}
+
+/// `FDIV`: `u / v`, an error if `v` is zero.
+pub fn fdiv(u: MIXWord, v: MIXWord, size: ByteSize) -> Result<Rounded, MixError> {
+    let format = Format::from(size);
+    let (su, eu, fu) = format.unpack(u);
+    let (sv, ev, fv) = format.unpack(v);
+    if fv == 0 {
+        return Err(MixError::DivideByZero);
+    }
+    let extra = DIGITS + 2;
+    let dividend = fu * format.pow(extra);
+    Ok(format.normalize(
+        su != sv,
+        eu - ev + format.excess,
+        dividend / fv,
+        extra,
+        !dividend.is_multiple_of(fv),
+    ))
+}
+
+/// `FLOT`: the integer `u` as a floating point number.
+pub fn flot(u: MIXWord, size: ByteSize) -> Rounded {
+    let format = Format::from(size);
+    format.normalize(
+        u.get_opposite() == 1,
+        format.excess,
+        u.get_unsinged() as u128,
+        0,
+        false,
+    )
+}
+
+/// ### Fix
+/// `FIX`: the floating point `u` rounded to the nearest integer, ties to even,
+/// with an overflow if it is too large for a word.
+/// ```rust
+/// use mixe::float::{fdiv, fix, flot};
+/// use mixe::mixword::ByteSize::Binary;
+/// use mixe::MIXWord;
+/// let (seven, _) = flot(MIXWord::from_value(-7), Binary);
+/// let (two, _) = flot(MIXWord::from_value(2), Binary);
+/// let (half, _) = fdiv(seven, two, Binary).unwrap();
+/// assert_eq!(fix(half, Binary), (MIXWord::from_value(-4), false));
+/// ```
+pub fn fix(u: MIXWord, size: ByteSize) -> Rounded {
+    let format = Format::from(size);
+    let (negative, e, f) = format.unpack(u);
+    let shift = e - format.excess - DIGITS as i64;
+    let (value, overflow) = match shift {
+        _ if f == 0 => (0, false),
+        5.. => (0, true),
+        0..=4 => {
+            let value = f * format.pow(shift as i32);
+            (value, value > size.max_value() as u128)
+        }
+        ..=-5 => (0, false),
+        _ => {
+            let unit = format.pow(-shift as i32);
+            let (q, rest) = (f / unit, f % unit);
+            match (2 * rest).cmp(&unit) {
+                Ordering::Greater => (q + 1, false),
+                Ordering::Equal if q % 2 == 1 => (q + 1, false),
+                _ => (q, false),
+            }
+        }
+    };
+    let mut word = MIXWord((value % size.word_size() as u128) as u64);
+    word.set_opposite(negative as u32);
+    (word, overflow)
+}
+
+/// ### Floating compare
+/// `FCMP`, after TAOCP 4.2.2: `u` and `v` are equal when `|u - v| <= eps * b^(max(eu, ev) - q)`.
+/// ```rust
+/// use mixe::float::{fcmp, flot};
+/// use mixe::mixword::ByteSize::Binary;
+/// use mixe::MIXWord;
+/// use std::cmp::Ordering;
+/// let (a, _) = flot(MIXWord::from_value(1000), Binary);
+/// let (b, _) = flot(MIXWord::from_value(1001), Binary);
+/// let (eps, _) = flot(MIXWord::from_value(0), Binary);
+/// assert_eq!(fcmp(a, b, eps, Binary), Ordering::Less);
+/// ```
+pub fn fcmp(u: MIXWord, v: MIXWord, eps: MIXWord, size: ByteSize) -> Ordering {
+    let format = Format::from(size);
+    let (value, e) = difference(u, v, format);
+    let (_, ee, fe) = format.unpack(eps);
+    let e_max = format.unpack(u).1.max(format.unpack(v).1);
+    // |u - v| = |value| * b^(e - q - p) against fe * b^(ee - q - p) * b^(e_max - q).
+    let shift = e - (ee + e_max - format.excess);
+    let distance = value.unsigned_abs();
+    let scaled = |value: u128, s: i64| {
+        u32::try_from(s)
+            .ok()
+            .and_then(|s| format.base.checked_pow(s))
+            .and_then(|unit| value.checked_mul(unit))
+    };
+    let within = match shift {
+        _ if distance == 0 => true,
+        0.. => scaled(distance, shift).is_some_and(|d| d <= fe),
+        _ => scaled(fe, -shift).is_none_or(|t| distance <= t),
+    };
+    if within {
+        Ordering::Equal
+    } else {
+        value.cmp(&0)
+    }
+}
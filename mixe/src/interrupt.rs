@@ -0,0 +1,114 @@
+use crate::mixword::{ByteSize, MIXWord};
+use std::collections::VecDeque;
+
+/// Locations -1 to -3999, usable only in control state.
+pub const CONTROL_MEMORY: usize = 3999;
+/// Counts down by the time of every instruction; reaching 0 interrupts to [`TIMER_HANDLER`].
+pub const TIMER: i64 = -10;
+pub const TIMER_HANDLER: i64 = -11;
+/// `INT` in normal state interrupts to this location.
+pub const INT_HANDLER: i64 = -12;
+/// Location -1 keeps the next location (1:2), `8 * OV + CI` (3:3) and rJ (4:5);
+/// rA, rI1 to rI6 and rX are kept in locations -9 to -2.
+pub const SAVED_STATE: i64 = -1;
+
+/// ### Unit handler
+/// the location an interrupt from unit `u` goes to when its operation completes.
+/// ```rust
+/// use mixe::interrupt::unit_handler;
+/// assert_eq!(unit_handler(18), -38);
+/// ```
+pub fn unit_handler(unit: usize) -> i64 {
+    -(20 + unit as i64)
+}
+
+/// ### Interrupts
+/// the interrupt facility of TAOCP exercise 1.4.4-18: control state, the negative
+/// locations, and the interrupts waiting to be taken. Interrupts are only taken in
+/// normal state; those raised in control state wait until `INT` returns.
+#[derive(Debug)]
+pub struct Interrupts {
+    pub control: bool,
+    memory: Vec<MIXWord>,
+    pending: VecDeque<i64>,
+    // (time, unit) of each I/O operation still to complete.
+    operations: Vec<(u64, usize)>,
+}
+
+impl Default for Interrupts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interrupts {
+    pub fn new() -> Self {
+        Interrupts {
+            control: false,
+            memory: vec![0.into(); CONTROL_MEMORY],
+            pending: VecDeque::new(),
+            operations: Vec::new(),
+        }
+    }
+
+    /// the word at negative `location`.
+    pub fn get(&self, location: i64) -> Option<&MIXWord> {
+        self.memory.get(Self::index(location)?)
+    }
+
+    pub fn get_mut(&mut self, location: i64) -> Option<&mut MIXWord> {
+        self.memory.get_mut(Self::index(location)?)
+    }
+
+    /// the handlers of the interrupts waiting, in the order they will be taken.
+    pub fn pending(&self) -> impl Iterator<Item = &i64> {
+        self.pending.iter()
+    }
+
+    /// request an interrupt to `handler`.
+    pub fn raise(&mut self, handler: i64) {
+        self.pending.push_back(handler);
+    }
+
+    /// interrupt to the unit's handler once time reaches `time`.
+    pub(crate) fn expect(&mut self, time: u64, unit: usize) {
+        self.operations.push((time, unit));
+    }
+
+    /// advance the timer, a word with bytes of the given size, and the I/O operations
+    /// from time `before` to `now`.
+    pub(crate) fn tick(&mut self, before: u64, now: u64, size: ByteSize) {
+        if let Some(timer) = self.get_mut(TIMER) {
+            let value = timer.get_value();
+            if value > 0 {
+                let left = value - (now - before) as i64;
+                *timer = MIXWord::from_value_for(left.max(0), size);
+                if left <= 0 {
+                    self.raise(TIMER_HANDLER);
+                }
+            }
+        }
+
+        self.operations.sort_by_key(|(time, _)| *time);
+        let done = self.operations.partition_point(|(time, _)| *time <= now);
+        for (_, unit) in self.operations.drain(..done).collect::<Vec<_>>() {
+            self.raise(unit_handler(unit));
+        }
+    }
+
+    /// the next interrupt to take, if the machine is in normal state.
+    pub(crate) fn take(&mut self) -> Option<i64> {
+        if self.control {
+            None
+        } else {
+            self.pending.pop_front()
+        }
+    }
+
+    fn index(location: i64) -> Option<usize> {
+        let index = location.checked_neg()? - 1;
+        (0..CONTROL_MEMORY as i64)
+            .contains(&index)
+            .then_some(index as usize)
+    }
+}
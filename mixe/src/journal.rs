@@ -0,0 +1,97 @@
+use crate::mixword::MIXWord;
+use crate::registers::Registers;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+pub const DEFAULT_CAPACITY: usize = 10_000;
+
+/// the machine as it was before an instruction, and the words it wrote.
+#[derive(Debug, Clone)]
+pub(crate) struct Entry {
+    pub location: i64,
+    pub register: Registers,
+    pub overflow: bool,
+    pub comp: Ordering,
+    pub time: u64,
+    pub running: bool,
+    /// the control state, when interrupts are enabled.
+    pub control: Option<bool>,
+    /// each written location with the word it held, in the order they were written.
+    pub memory: Vec<(i64, MIXWord)>,
+}
+
+/// ### Journal
+/// what the last `capacity` instructions changed, so that [`crate::MIXCPU::step_back`]
+/// can undo them. Devices and the interrupts waiting to be taken are not rewound.
+/// A capacity of 0 turns the journal off.
+/// ```rust
+/// use mixe::journal::Journal;
+/// use mixe::{MIXComputer, MIXCPU};
+/// let mut computer = MIXCPU::from(MIXComputer::new());
+/// computer.journal = Journal::new(2);
+/// for _ in 0..3 {
+///     computer.step().unwrap(); // NOP
+/// }
+/// assert_eq!(computer.journal.len(), 2);
+/// assert!(computer.step_back() && computer.step_back() && !computer.step_back());
+/// assert_eq!(computer.location, 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Journal {
+    entries: VecDeque<Entry>,
+    capacity: usize,
+}
+
+impl Default for Journal {
+    fn default() -> Self {
+        Journal::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl Journal {
+    pub fn new(capacity: usize) -> Self {
+        Journal {
+            entries: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// keep at most `capacity` instructions, forgetting the oldest.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    /// the number of instructions that can be undone.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub(crate) fn push(&mut self, entry: Entry) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<Entry> {
+        self.entries.pop_back()
+    }
+}
@@ -1,15 +1,28 @@
+pub mod assembler;
+pub mod breakpoint;
+pub mod charset;
 pub mod command_parser;
+pub mod disassembler;
+pub mod error;
+pub mod expression;
+pub mod float;
+pub mod interrupt;
+pub mod journal;
 pub mod mixcomputer;
 pub mod mixcpu;
 pub mod mixword;
+pub mod registers;
 pub mod unit;
+pub mod watch;
 
 pub use std::error::Error;
 
 // use mixword::MIXWord;
+pub use error::MixError;
 pub use mixcomputer::MIXComputer;
 pub use mixcpu::MIXCPU;
 pub use mixword::MIXWord;
+pub use registers::Registers;
 pub use unit::Unit;
 
 #[cfg(test)]
@@ -37,6 +50,15 @@ mod tests {
             <MIXWord as From<(u32, u32, u32, u32, u32, u32)>>::from((1, 0, 2000, 4, 5, 8)),
             ("LDA -2000,4").try_into().unwrap()
         );
+        // numbers too big for a u32 are errors, not overflows.
+        assert!(matches!(
+            command_parser::parse("LDA 99999999999"),
+            Err(MixError::Parse(_))
+        ));
+        assert!(matches!(
+            command_parser::parse("LDA 0(999999999:1)"),
+            Err(MixError::Parse(_))
+        ));
     }
     #[test]
     fn test_load() {
@@ -61,7 +83,10 @@ mod tests {
     fn test_store() {
         let mut computer = MIXComputer::new();
         computer.memory[2000] = (1, 1, 2, 3, 4, 5).into();
-        computer.register[0] = (0, 6, 7, 8, 9, 0).into();
+        computer
+            .register
+            .set(0, (0, 6, 7, 8, 9, 0).into(), mixword::ByteSize::Binary)
+            .unwrap();
         let mut computer = MIXCPU::from(computer);
         computer.run_command("STA 2000").unwrap();
         // 00_000000_001001_001000_000111_000110
@@ -71,35 +96,50 @@ mod tests {
 
         let mut computer = MIXComputer::new();
         computer.memory[2000] = (1, 1, 2, 3, 4, 5).into();
-        computer.register[0] = (0, 6, 7, 8, 9, 0).into();
+        computer
+            .register
+            .set(0, (0, 6, 7, 8, 9, 0).into(), mixword::ByteSize::Binary)
+            .unwrap();
         let mut computer = MIXCPU::from(computer);
         computer.run_command("STA 2000(1:5)").unwrap();
         assert_eq!(computer.computer.memory[2000], (1, 6, 7, 8, 9, 0).into());
 
         let mut computer = MIXComputer::new();
         computer.memory[2000] = (1, 1, 2, 3, 4, 5).into();
-        computer.register[0] = (0, 6, 7, 8, 9, 0).into();
+        computer
+            .register
+            .set(0, (0, 6, 7, 8, 9, 0).into(), mixword::ByteSize::Binary)
+            .unwrap();
         let mut computer = MIXCPU::from(computer);
         computer.run_command("STA 2000(5:5)").unwrap();
         assert_eq!(computer.computer.memory[2000], (1, 1, 2, 3, 4, 0).into());
 
         let mut computer = MIXComputer::new();
         computer.memory[2000] = (1, 1, 2, 3, 4, 5).into();
-        computer.register[0] = (0, 6, 7, 8, 9, 0).into();
+        computer
+            .register
+            .set(0, (0, 6, 7, 8, 9, 0).into(), mixword::ByteSize::Binary)
+            .unwrap();
         let mut computer = MIXCPU::from(computer);
         computer.run_command("STA 2000(2:2)").unwrap();
         assert_eq!(computer.computer.memory[2000], (1, 1, 0, 3, 4, 5).into());
 
         let mut computer = MIXComputer::new();
         computer.memory[2000] = (1, 1, 2, 3, 4, 5).into();
-        computer.register[0] = (0, 6, 7, 8, 9, 0).into();
+        computer
+            .register
+            .set(0, (0, 6, 7, 8, 9, 0).into(), mixword::ByteSize::Binary)
+            .unwrap();
         let mut computer = MIXCPU::from(computer);
         computer.run_command("STA 2000(2:3)").unwrap();
         assert_eq!(computer.computer.memory[2000], (1, 1, 9, 0, 4, 5).into());
 
         let mut computer = MIXComputer::new();
         computer.memory[2000] = (1, 1, 2, 3, 4, 5).into();
-        computer.register[0] = (0, 6, 7, 8, 9, 0).into();
+        computer
+            .register
+            .set(0, (0, 6, 7, 8, 9, 0).into(), mixword::ByteSize::Binary)
+            .unwrap();
         let mut computer = MIXCPU::from(computer);
         computer.run_command("STA 2000(0:1)").unwrap();
         assert_eq!(computer.computer.memory[2000], (0, 0, 2, 3, 4, 5).into());
@@ -107,7 +147,10 @@ mod tests {
     #[test]
     fn test_add() {
         let mut computer = MIXComputer::new();
-        computer.register[0] = (0, 0, 1234, 1, 0, 150).into();
+        computer
+            .register
+            .set(0, (0, 0, 1234, 1, 0, 150).into(), mixword::ByteSize::Binary)
+            .unwrap();
         computer.memory[1000] = (0, 0, 100, 5, 0, 50).into();
         let mut computer = MIXCPU::from(computer);
         computer.run_command("ADD 1000").unwrap();
@@ -119,7 +162,10 @@ mod tests {
     #[test]
     fn test_sub() {
         let mut computer = MIXComputer::new();
-        computer.register[0] = (1, 0, 1234, 0, 0, 9).into();
+        computer
+            .register
+            .set(0, (1, 0, 1234, 0, 0, 9).into(), mixword::ByteSize::Binary)
+            .unwrap();
         computer.memory[1000] = (1, 0, 2000, 0, 150, 0).into();
         let mut computer = MIXCPU::from(computer);
         computer.run_command("SUB 1000").unwrap();
@@ -131,7 +177,10 @@ mod tests {
     #[test]
     fn test_mul() {
         let mut computer = MIXComputer::new();
-        computer.register[0] = (0, 1, 1, 1, 1, 1).into();
+        computer
+            .register
+            .set(0, (0, 1, 1, 1, 1, 1).into(), mixword::ByteSize::Binary)
+            .unwrap();
         computer.memory[1000] = (0, 1, 1, 1, 1, 1).into();
         let mut computer = MIXCPU::from(computer);
         computer.run_command("MUL 1000").unwrap();
@@ -141,12 +190,1396 @@ mod tests {
     #[test]
     fn test_div() {
         let mut computer = MIXComputer::new();
-        computer.register[0] = (0, 0, 0, 0, 0, 0).into();
-        computer.register[7] = (1, 0, 0, 0, 0, 17).into();
+        computer
+            .register
+            .set(0, (0, 0, 0, 0, 0, 0).into(), mixword::ByteSize::Binary)
+            .unwrap();
+        computer
+            .register
+            .set(7, (1, 0, 0, 0, 0, 17).into(), mixword::ByteSize::Binary)
+            .unwrap();
         computer.memory[1000] = (0, 0, 0, 0, 0, 3).into();
         let mut computer = MIXCPU::from(computer);
         computer.run_command("DIV 1000").unwrap();
         assert_eq!(computer.computer.register[0], (0, 0, 0, 0, 0, 5).into());
         assert_eq!(computer.computer.register[7], (0, 0, 0, 0, 0, 2).into());
+
+        // a zero quotient still takes the sign of rA times the sign of V.
+        computer
+            .computer
+            .register
+            .set(0, (1, 0, 0, 0, 0, 0).into(), mixword::ByteSize::Binary)
+            .unwrap();
+        computer
+            .computer
+            .register
+            .set(7, (0, 0, 0, 0, 0, 2).into(), mixword::ByteSize::Binary)
+            .unwrap();
+        computer.run_command("DIV 1000").unwrap();
+        assert_eq!(computer.computer.register[0], (1, 0, 0, 0, 0, 0).into());
+        assert_eq!(computer.computer.register[7], (1, 0, 0, 0, 0, 2).into());
+    }
+
+    #[test]
+    fn test_compare() {
+        use std::cmp::Ordering;
+        let mut computer = MIXComputer::new();
+        computer
+            .register
+            .set(0, (1, 1, 2, 3, 4, 5).into(), mixword::ByteSize::Binary)
+            .unwrap();
+        computer
+            .register
+            .set(1, (0, 0, 0, 0, 4, 7).into(), mixword::ByteSize::Binary)
+            .unwrap();
+        computer.memory[1000] = (0, 1, 2, 3, 4, 6).into();
+        let mut computer = MIXCPU::from(computer);
+        computer.run_command("CMP1 1000(4:5)").unwrap();
+        assert_eq!(computer.computer.comp, Ordering::Greater);
+        computer.run_command("CMPA 1000").unwrap();
+        assert_eq!(computer.computer.comp, Ordering::Less);
+        computer.run_command("CMPA 1000(1:4)").unwrap();
+        assert_eq!(computer.computer.comp, Ordering::Equal);
+    }
+
+    #[test]
+    fn test_num_char() {
+        // the example of TAOCP 1.3.1.
+        let mut computer = MIXComputer::new();
+        computer
+            .register
+            .set(0, (1, 0, 0, 31, 32, 39).into(), mixword::ByteSize::Binary)
+            .unwrap();
+        computer
+            .register
+            .set(7, (0, 37, 57, 47, 30, 30).into(), mixword::ByteSize::Binary)
+            .unwrap();
+        let mut computer = MIXCPU::from(computer);
+        computer.run_command("NUM 0").unwrap();
+        assert_eq!(
+            computer.computer.register[0],
+            MIXWord::from_value(-12977700)
+        );
+        assert_eq!(
+            computer.computer.register[7],
+            (0, 37, 57, 47, 30, 30).into()
+        );
+        computer.run_command("INCA 1").unwrap();
+        computer.run_command("CHAR 0").unwrap();
+        assert_eq!(
+            computer.computer.register[0],
+            (1, 30, 30, 31, 32, 39).into()
+        );
+        assert_eq!(
+            computer.computer.register[7],
+            (0, 37, 37, 36, 39, 39).into()
+        );
+        assert!(!computer.computer.overflow);
+
+        let mut computer = MIXComputer::new();
+        computer
+            .register
+            .set(0, (0, 39, 39, 39, 39, 39).into(), mixword::ByteSize::Binary)
+            .unwrap();
+        computer
+            .register
+            .set(7, (0, 39, 39, 39, 39, 39).into(), mixword::ByteSize::Binary)
+            .unwrap();
+        let mut computer = MIXCPU::from(computer);
+        computer.run_command("NUM 0").unwrap();
+        assert!(computer.computer.overflow);
+        assert_eq!(
+            computer.computer.register[0],
+            MIXWord::from_value(9999999999 % (1 << 30))
+        );
+    }
+
+    #[test]
+    fn test_card_reader() {
+        let mut computer = MIXCPU::from(MIXComputer::new());
+        let cards = std::io::Cursor::new("HELLO, WORLD\n0123456789\n");
+        computer.computer.units[16].attach(Box::new(unit::CardReader::new(cards)));
+
+        computer.run_command("IN 1000(16)").unwrap();
+        let card = charset::from_words(
+            &computer.computer.memory[1000..1016],
+            charset::Unmappable::Error,
+        )
+        .unwrap();
+        assert_eq!(card, format!("{:80}", "HELLO, WORLD"));
+        computer.run_command("IN 1000(16)").unwrap();
+        assert_eq!(
+            computer.computer.memory[1000],
+            (0, 30, 31, 32, 33, 34).into()
+        );
+        assert_eq!(computer.computer.memory[1002], (0, 0, 0, 0, 0, 0).into());
+        assert!(computer.run_command("IN 1000(16)").is_err());
+        assert!(computer.run_command("IN 3990(16)").is_err());
+    }
+
+    #[test]
+    fn test_line_printer() {
+        // Program P of TAOCP 1.3.2: the first five hundred primes.
+        let program = assembler::assemble(
+            "
+L          EQU  500
+PRINTER    EQU  18
+PRIME      EQU  -1
+BUF0       EQU  2000
+BUF1       EQU  BUF0+25
+           ORIG 3000
+START      IOC  0(PRINTER)
+           LD1  =1-L=
+           LD2  =3=
+2H         INC1 1
+           ST2  PRIME+L,1
+           J1Z  2F
+4H         INC2 2
+           ENT3 2
+6H         ENTA 0
+           ENTX 0,2
+           DIV  PRIME,3
+           JXZ  4B
+           CMPA PRIME,3
+           INC3 1
+           JG   6B
+           JMP  2B
+2H         OUT  TITLE(PRINTER)
+           ENT4 BUF1+10
+           ENT5 -50
+2H         INC5 L+1
+4H         LDA  PRIME,5
+           CHAR
+           STX  0,4(1:4)
+           DEC4 1
+           DEC5 50
+           J5P  4B
+           OUT  0,4(PRINTER)
+           LD4  24,4
+           J5N  2B
+           HLT
+           ORIG PRIME+1
+           CON  2
+           ORIG BUF0-5
+TITLE      ALF FIRST
+           ALF  FIVE
+           ALF  HUND
+           ALF RED P
+           ALF RIMES
+           ORIG BUF0+24
+           CON  BUF1+10
+           ORIG BUF1+24
+           CON  BUF0+10
+           END  START
+",
+        )
+        .unwrap();
+        let mut computer = MIXCPU::from(program.computer);
+        let output = unit::SharedBuffer::default();
+        computer.computer.units[18].attach(Box::new(unit::LinePrinter::new(output.clone())));
+        computer.location = program.start as i64;
+        computer.start();
+
+        let output = output.contents();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 51);
+        assert_eq!(lines[0], "\x0cFIRST FIVE HUNDRED PRIMES");
+        assert_eq!(
+            lines[1],
+            "     0002 0233 0547 0877 1229 1597 1993 2371 2749 3187"
+        );
+        assert_eq!(
+            lines[50],
+            "     0229 0541 0863 1223 1583 1987 2357 2741 3181 3571"
+        );
+    }
+
+    #[test]
+    fn test_tape() {
+        let path = std::env::temp_dir().join(format!("mixe-tape-{}.img", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut computer = MIXCPU::from(MIXComputer::new());
+        computer.computer.units[3].attach(Box::new(unit::Tape::open(&path).unwrap()));
+
+        for i in 0..3 {
+            computer.computer.memory[1000] = MIXWord::from_value(-i);
+            computer.computer.memory[1099] = MIXWord::from_value(i * 100);
+            computer.run_command("OUT 1000(3)").unwrap();
+        }
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().len(),
+            (3 * unit::TAPE_BLOCK * unit::WORD_BYTES) as u64
+        );
+        assert!(computer.run_command("IN 2000(3)").is_err());
+
+        computer.run_command("IOC -2(3)").unwrap();
+        computer.run_command("IN 2000(3)").unwrap();
+        assert_eq!(computer.computer.memory[2000], MIXWord::from_value(-1));
+        assert_eq!(computer.computer.memory[2099], MIXWord::from_value(100));
+
+        computer.run_command("IOC 0(3)").unwrap();
+        computer.run_command("IOC 1(3)").unwrap();
+        computer.run_command("OUT 1000(3)").unwrap();
+        computer.run_command("IOC -5(3)").unwrap();
+        computer.run_command("IN 2000(3)").unwrap();
+        assert_eq!(computer.computer.memory[2099], MIXWord::from_value(0));
+        computer.run_command("IN 2000(3)").unwrap();
+        assert_eq!(computer.computer.memory[2099], MIXWord::from_value(200));
+        assert!(computer.run_command("IN 2000(3)").is_err());
+        assert!(computer.run_command("IOC 1(3)").is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_disk() {
+        let path = std::env::temp_dir().join(format!("mixe-disk-{}.img", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut computer = MIXCPU::from(MIXComputer::new());
+        computer.computer.units[9].attach(Box::new(unit::Disk::open(&path).unwrap()));
+
+        computer.computer.memory[1000] = MIXWord::from_value(-7);
+        computer.computer.memory[1099] = (0, 1, 2, 3, 4, 5).into();
+        computer.run_command("ENTX 5").unwrap();
+        computer.run_command("OUT 1000(9)").unwrap();
+        computer.run_command("ENTX 2").unwrap();
+        computer.run_command("IN 2000(9)").unwrap();
+        assert_eq!(computer.computer.memory[2000], 0.into());
+        computer.run_command("ENTX 5").unwrap();
+        computer.run_command("IN 2000(9)").unwrap();
+        assert_eq!(computer.computer.memory[2000], MIXWord::from_value(-7));
+        assert_eq!(computer.computer.memory[2099], (0, 1, 2, 3, 4, 5).into());
+
+        computer.run_command("ENTX 40").unwrap();
+        computer.run_command("IOC 0(9)").unwrap();
+        let disk = computer.computer.units[9].device::<unit::Disk>().unwrap();
+        assert_eq!(disk.arm(), 40);
+        computer.run_command("ENNX 1").unwrap();
+        assert!(computer.run_command("IN 2000(9)").is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_terminal_and_paper_tape() {
+        let mut computer = MIXCPU::from(MIXComputer::new());
+        let output = unit::SharedBuffer::default();
+        let input = std::io::Cursor::new("YES\n");
+        computer.computer.units[19].attach(Box::new(unit::Terminal::new(input, output.clone())));
+        computer.run_command("IN 1000(19)").unwrap();
+        assert_eq!(computer.computer.memory[1000], (0, 28, 5, 22, 0, 0).into());
+        computer.computer.memory[1000].set_range(4, 5, (0, 0, 0, 0, 40, 40).into());
+        computer.run_command("OUT 1000(19)").unwrap();
+        assert_eq!(output.contents(), "YES..\n");
+
+        let path = std::env::temp_dir().join(format!("mixe-paper-{}.txt", std::process::id()));
+        std::fs::write(&path, "FIRST\nSECOND\n").unwrap();
+        computer.computer.units[20].attach(Box::new(unit::PaperTape::open(&path).unwrap()));
+        computer.run_command("IN 2000(20)").unwrap();
+        computer.run_command("IN 2000(20)").unwrap();
+        assert_eq!(computer.computer.memory[2000], (0, 22, 5, 3, 16, 15).into());
+        assert!(computer.run_command("IN 2000(20)").is_err());
+        computer.run_command("IOC 0(20)").unwrap();
+        computer.run_command("IN 2000(20)").unwrap();
+        assert_eq!(computer.computer.memory[2000], (0, 6, 9, 19, 22, 23).into());
+        assert!(computer.run_command("OUT 2000(20)").is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[derive(Debug, Default)]
+    struct Plotter {
+        points: Vec<i64>,
+        busy: bool,
+    }
+
+    impl unit::Device for Plotter {
+        fn block_size(&self) -> usize {
+            2
+        }
+
+        fn write_block(
+            &mut self,
+            block: &[MIXWord],
+            _x: i64,
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            self.points
+                .extend(block.iter().map(|word| word.get_value()));
+            Ok(())
+        }
+
+        fn busy(&self) -> bool {
+            self.busy
+        }
+    }
+
+    #[test]
+    fn test_custom_device() {
+        let mut computer = MIXCPU::from(MIXComputer::new());
+        computer.computer.units[17].attach(Box::new(Plotter::default()));
+        computer.computer.memory[100] = MIXWord::from_value(3);
+        computer.computer.memory[101] = MIXWord::from_value(-4);
+        computer.run_command("OUT 100(17)").unwrap();
+        assert!(computer.run_command("IN 100(17)").is_err());
+        assert!(computer.run_command("IOC 0(17)").is_err());
+
+        computer.location = 10;
+        computer.run_command("JBUS 500(17)").unwrap();
+        assert_eq!(computer.location, 10);
+        computer.run_command("JRED 500(17)").unwrap();
+        assert_eq!(computer.location, 499);
+
+        let plotter = computer.computer.units[17].device_mut::<Plotter>().unwrap();
+        assert_eq!(plotter.points, vec![3, -4]);
+        plotter.busy = true;
+        computer.run_command("JBUS 600(17)").unwrap();
+        assert_eq!(computer.location, 599);
+        assert!(computer.run_command("OUT 100(17)").is_err());
+        // a jump out of range leaves rJ as it was.
+        let j = computer.computer.register[8];
+        assert!(computer.run_command("JBUS 4000(17)").is_err());
+        assert_eq!(computer.computer.register[8], j);
+        assert!(computer.computer.units[17].device::<unit::Tape>().is_none());
+        assert!(computer.computer.units[17].detach().is_some());
+    }
+
+    #[test]
+    fn test_memory_device() {
+        let program = assembler::assemble(
+            "\
+CARDS      EQU  16
+PRINTER    EQU  18
+           ORIG 1000
+START      IOC  0(PRINTER)
+1H         IN   BUF(CARDS)
+           LDA  BUF
+           JAZ  DONE
+           OUT  BUF(PRINTER)
+           JMP  1B
+DONE       HLT
+BUF        ORIG *+24
+           END  START
+",
+        )
+        .unwrap();
+        let mut computer = MIXCPU::from(program.computer);
+        let cards = unit::MemoryDevice::from_text(16, &["FIRST", "SECOND CARD", ""]).unwrap();
+        computer.computer.units[16].attach(Box::new(cards));
+        computer.computer.units[18].attach(Box::new(unit::MemoryDevice::new(24)));
+        computer.location = program.start as i64;
+        computer.start();
+
+        let cards = computer.computer.units[16]
+            .device::<unit::MemoryDevice>()
+            .unwrap();
+        cards.assert_consumed();
+        let printer = computer.computer.units[18]
+            .device::<unit::MemoryDevice>()
+            .unwrap();
+        printer.assert_lines(&["FIRST", "SECOND CARD"]);
+        assert_eq!(printer.controls(), &[0]);
+        assert_eq!(printer.blocks()[0][0], (0, 6, 9, 19, 22, 23).into());
+        assert!(unit::MemoryDevice::with_blocks(2, vec![vec![0.into()]]).is_err());
+    }
+
+    #[test]
+    fn test_time() {
+        let program = assembler::assemble(
+            "\
+* A COUNTDOWN LOOP, THEN ONE OF EACH KIND
+           ORIG 3000
+START      ENT1 10
+1H         DEC1 1
+           J1P  1B
+           LDA  =7=
+           MUL  =3=
+           ENT1 3100
+           MOVE 0(5)
+           HLT
+           END  START
+",
+        )
+        .unwrap();
+        let mut computer = MIXCPU::from(program.computer);
+        computer.location = program.start as i64;
+        computer.start();
+        // 1 + 10 * (1 + 1) + 2 + 10 + 1 + (1 + 2 * 5) + 10
+        assert_eq!(computer.time, 55);
+        computer.reset_time();
+        computer.run_command("DIV 3000").unwrap();
+        assert_eq!(computer.time, 12);
+    }
+
+    #[test]
+    fn test_busy_units() {
+        let program = assembler::assemble(
+            "\
+START      OUT  BUF(18)
+           ENT1 0
+1H         INC1 1
+           JBUS 1B(18)
+           OUT  BUF(18)
+           OUT  BUF(18)
+           JRED 2F(18)
+           ENT2 1
+2H         HLT
+BUF        ALF  \"READY\"
+           END  START
+",
+        )
+        .unwrap();
+        let mut computer = MIXCPU::from(program.computer);
+        let printer = unit::MemoryDevice::new(24).with_operation_time(100);
+        computer.computer.units[18].attach(Box::new(printer));
+        computer.location = program.start as i64;
+        computer.start();
+
+        // the loop waits from 2u to 102u; the third OUT waits for the second.
+        assert_eq!(computer.computer.register[1].get_value(), 50);
+        assert_eq!(computer.computer.register[2].get_value(), 1);
+        assert_eq!(computer.time, 215);
+        assert!(computer.computer.units[18].busy(300));
+        assert!(!computer.computer.units[18].busy(303));
+        let printer = computer.computer.units[18]
+            .device::<unit::MemoryDevice>()
+            .unwrap();
+        assert_eq!(printer.lines()[2], "READY");
+    }
+
+    #[test]
+    fn test_interrupts() {
+        let program = assembler::assemble(
+            "\
+START      ENTA 7
+           ENT1 3
+           INT
+           INC1 1
+           HLT
+* INT HANDLER, RUN IN CONTROL STATE
+           ORIG 3000
+           ENTA 42
+           STA  2000
+           LDA  -9
+           STA  2001
+           INT
+* TIMER HANDLER
+           ORIG 3100
+           ST1  2002
+           INT
+           END  START
+",
+        )
+        .unwrap();
+        let mut computer = MIXCPU::from(program.computer);
+        computer.enable_interrupts();
+        let interrupts = computer.interrupts.as_mut().unwrap();
+        *interrupts.get_mut(interrupt::INT_HANDLER).unwrap() = "JMP 3000".try_into().unwrap();
+        *interrupts.get_mut(interrupt::TIMER_HANDLER).unwrap() = "JMP 3100".try_into().unwrap();
+        *interrupts.get_mut(interrupt::TIMER).unwrap() = MIXWord::from_value(10);
+        computer.location = program.start as i64;
+        computer.start();
+
+        // the timer runs out inside the INT handler and waits for it to return.
+        assert_eq!(computer.computer.memory[2000], MIXWord::from_value(42));
+        assert_eq!(computer.computer.memory[2001], MIXWord::from_value(7));
+        assert_eq!(computer.computer.memory[2002], MIXWord::from_value(3));
+        assert_eq!(computer.computer.register[0], MIXWord::from_value(7));
+        assert_eq!(computer.computer.register[1], MIXWord::from_value(4));
+        let interrupts = computer.interrupts.as_ref().unwrap();
+        assert!(!interrupts.control);
+        assert_eq!(interrupts.pending().count(), 0);
+        assert_eq!(
+            *interrupts.get(interrupt::TIMER).unwrap(),
+            MIXWord::from_value(0)
+        );
+        assert!(computer.run_command("LDA -9").is_err());
+        assert!(MIXCPU::from(MIXComputer::new())
+            .run_command("INT 0")
+            .is_err());
+
+        // a decimal timer counts down from past 2^30.
+        let mut computer = MIXCPU::from(MIXComputer::with_byte_size(mixword::ByteSize::Decimal));
+        computer.enable_interrupts();
+        let timer = MIXWord::from_value_for(2_000_000_000, mixword::ByteSize::Decimal);
+        *computer
+            .interrupts
+            .as_mut()
+            .unwrap()
+            .get_mut(interrupt::TIMER)
+            .unwrap() = timer;
+        // memory[0] is `NOP 0`.
+        computer.step().unwrap();
+        let timer = *computer
+            .interrupts
+            .as_ref()
+            .unwrap()
+            .get(interrupt::TIMER)
+            .unwrap();
+        assert_eq!(timer.get_value(), 1_999_999_999);
+
+        // a saved rI1 too wide to restore stops INT before any register changes.
+        let program = assembler::assemble(
+            "\
+START      ENT1 3
+           INT
+           HLT
+WIDE       CON  4096
+           ORIG 3000
+           LDA  WIDE
+           STA  -8
+           INT
+           END  START
+",
+        )
+        .unwrap();
+        let mut computer = MIXCPU::from(program.computer);
+        computer.enable_interrupts();
+        let interrupts = computer.interrupts.as_mut().unwrap();
+        *interrupts.get_mut(interrupt::INT_HANDLER).unwrap() = "JMP 3000".try_into().unwrap();
+        computer.location = program.start as i64;
+        computer.start();
+        assert!(computer.interrupts.as_ref().unwrap().control);
+        assert_eq!(computer.computer.register.a(), MIXWord::from_value(4096));
+        assert_eq!(
+            computer.computer.register.i(1),
+            Some(MIXWord::from_value(3))
+        );
+    }
+
+    #[test]
+    fn test_device_interrupt() {
+        let program = assembler::assemble(
+            "\
+START      OUT  BUF(18)
+           ENT2 0
+1H         INC2 1
+           LDA  FLAG
+           JANZ 1B
+           HLT
+FLAG       CON  1
+BUF        CON  0
+* UNIT 18 HANDLER
+           ORIG 3000
+           STZ  FLAG
+           INT
+           END  START
+",
+        )
+        .unwrap();
+        let mut computer = MIXCPU::from(program.computer);
+        let printer = unit::MemoryDevice::new(24).with_operation_time(20);
+        computer.computer.units[18].attach(Box::new(printer));
+        computer.enable_interrupts();
+        *computer
+            .interrupts
+            .as_mut()
+            .unwrap()
+            .get_mut(interrupt::unit_handler(18))
+            .unwrap() = "JMP 3000".try_into().unwrap();
+        computer.location = program.start as i64;
+        computer.start();
+
+        // the OUT completes at 21u, after the LDA of the fifth pass; rA is
+        // restored on return, so one more pass sees the cleared flag.
+        assert_eq!(computer.computer.register[2].get_value(), 6);
+        assert_eq!(computer.interrupts.as_ref().unwrap().pending().count(), 0);
+    }
+
+    #[test]
+    fn test_float() {
+        let mut computer = MIXCPU::from(MIXComputer::new());
+        computer.computer.memory[1000] = MIXWord::from_value(1);
+        computer.computer.memory[1001] = MIXWord::from_value(3);
+        for command in [
+            "LDA 1001",
+            "FLOT 0",
+            "STA 1001",
+            "LDA 1000",
+            "FLOT 0",
+            "FDIV 1001",
+        ] {
+            computer.run_command(command).unwrap();
+        }
+        assert_eq!(
+            computer.computer.register[0],
+            (0, 32, 21, 21, 21, 21).into()
+        );
+        computer.run_command("FMUL 1001").unwrap();
+        assert_eq!(
+            computer.computer.register[0],
+            (0, 32, 63, 63, 63, 63).into()
+        );
+        computer.run_command("FIX 0").unwrap();
+        assert_eq!(computer.computer.register[0], MIXWord::from_value(1));
+        computer.run_command("LDA 1001").unwrap();
+        computer.run_command("FSUB 1001").unwrap();
+        assert_eq!(computer.computer.register[0], 0.into());
+        assert!(computer.run_command("FDIV 1000").is_ok());
+        assert!(computer.run_command("FDIV 1002").is_err());
+        assert!(!computer.computer.overflow);
+
+        computer.computer.memory[1002] = (0, 63, 1, 0, 0, 0).into();
+        computer.run_command("LDA 1002").unwrap();
+        computer.run_command("FMUL 1002").unwrap();
+        assert!(computer.computer.overflow);
+
+        // 1000 and 1001 have exponent q + 2, so eps = 1/64 makes them equal.
+        computer.computer.memory[1000] = (0, 34, 15, 40, 0, 0).into();
+        computer.computer.memory[1001] = (0, 34, 15, 41, 0, 0).into();
+        computer.run_command("LDA 1000").unwrap();
+        computer.run_command("FCMP 1001").unwrap();
+        assert_eq!(computer.computer.comp, std::cmp::Ordering::Less);
+        computer.computer.memory[0] = (0, 32, 1, 0, 0, 0).into();
+        computer.run_command("FCMP 1001").unwrap();
+        assert_eq!(computer.computer.comp, std::cmp::Ordering::Equal);
+
+        let program = assembler::assemble(" FADD 1000\n FCMP 1\n FIX\n END 0").unwrap();
+        assert_eq!(program.computer.memory[0], (0, 0, 1000, 0, 6, 1).into());
+        assert_eq!(disassembler::render(program.computer.memory[1]), "FCMP 1");
+        assert_eq!(disassembler::render(program.computer.memory[2]), "FIX 0");
+    }
+
+    #[test]
+    fn test_shift() {
+        // the example of TAOCP 1.3.1.
+        let mut computer = MIXCPU::from(MIXComputer::new());
+        computer
+            .computer
+            .register
+            .set(0, (0, 1, 2, 3, 4, 5).into(), mixword::ByteSize::Binary)
+            .unwrap();
+        computer
+            .computer
+            .register
+            .set(7, (1, 6, 7, 8, 9, 10).into(), mixword::ByteSize::Binary)
+            .unwrap();
+        let steps: [(&str, MIXWord, MIXWord); 5] = [
+            (
+                "SRAX 1",
+                (0, 0, 1, 2, 3, 4).into(),
+                (1, 5, 6, 7, 8, 9).into(),
+            ),
+            (
+                "SLA 2",
+                (0, 2, 3, 4, 0, 0).into(),
+                (1, 5, 6, 7, 8, 9).into(),
+            ),
+            (
+                "SRC 4",
+                (0, 6, 7, 8, 9, 2).into(),
+                (1, 3, 4, 0, 0, 5).into(),
+            ),
+            (
+                "SRA 2",
+                (0, 0, 0, 6, 7, 8).into(),
+                (1, 3, 4, 0, 0, 5).into(),
+            ),
+            (
+                "SLC 501",
+                (0, 0, 6, 7, 8, 3).into(),
+                (1, 4, 0, 0, 5, 0).into(),
+            ),
+        ];
+        for (command, a, x) in steps {
+            computer.run_command(command).unwrap();
+            assert_eq!(computer.computer.register[0], a, "{}", command);
+            assert_eq!(computer.computer.register[7], x, "{}", command);
+        }
+
+        computer
+            .computer
+            .register
+            .set(0, 0.into(), mixword::ByteSize::Binary)
+            .unwrap();
+        computer
+            .computer
+            .register
+            .set(7, 1.into(), mixword::ByteSize::Binary)
+            .unwrap();
+        computer.run_command("SLB 31").unwrap();
+        assert_eq!(computer.computer.register[0], 2.into());
+        assert_eq!(computer.computer.register[7], 0.into());
+        computer.run_command("SRB 1").unwrap();
+        assert_eq!(computer.computer.register[0], 1.into());
+        assert!(computer.run_command("SLB -1").is_err());
+
+        computer.location = 10;
+        computer.run_command("JAE 100").unwrap();
+        assert_eq!(computer.location, 10);
+        computer.run_command("JAO 100").unwrap();
+        assert_eq!(computer.location, 99);
+        computer.run_command("JXE 200").unwrap();
+        assert_eq!(computer.location, 199);
+        assert_eq!(disassembler::render("J3O 5".try_into().unwrap()), "J3O 5");
+        assert_eq!(disassembler::render("SRB 3".try_into().unwrap()), "SRB 3");
+    }
+
+    #[test]
+    fn test_assemble() {
+        let program = assembler::assemble(
+            "
+* sum a table of five numbers
+N       EQU  5
+        ORIG 1000
+TABLE   CON  1
+        CON  2
+        CON  3
+        CON  4
+        CON  5
+TITLE   ALF SUM..
+        ORIG 3000
+START   ENT1 N
+        ENTA 0
+2H      ADD  TABLE-1,1
+        DEC1 1
+        J1P  2B
+        STA  RESULT
+        JMP  1F
+        HLT
+1H      LDX  =1(1:1),2(5:5)=
+        HLT
+        END  START
+",
+        )
+        .unwrap();
+        assert_eq!(program.start, 3000);
+        assert_eq!(program.symbols()["TITLE"], 1005);
+        assert_eq!(
+            program.computer.memory[1005],
+            (0, 22, 24, 14, 40, 40).into()
+        );
+        assert_eq!(program.computer.memory[3002], (0, 0, 999, 1, 5, 1).into());
+        assert_eq!(program.computer.memory[3008].get_aa(), 3011);
+
+        let mut computer = MIXCPU::from(program.computer);
+        computer.location = program.start as i64;
+        computer.start();
+        assert_eq!(computer.computer.memory[3010], 15.into());
+        assert_eq!(computer.computer.register[7], (0, 1, 0, 0, 0, 2).into());
+    }
+
+    #[test]
+    fn test_listing() {
+        let listing = assembler::listing(
+            "* two literals
+        ORIG 100
+START   LDA  =5=
+        ADD  =5=
+        STA  SUM
+        END  START",
+        )
+        .unwrap();
+        assert_eq!(listing.lines.len(), 9);
+        assert_eq!(listing.lines[0].location, None);
+        assert_eq!(listing.lines[2].location, Some(100));
+        assert_eq!(listing.lines[2].word, Some((0, 1, 39, 0, 5, 8).into()));
+        assert_eq!(listing.lines[5].word, None);
+        assert_eq!(listing.lines[6].location, Some(103));
+        assert_eq!(listing.lines[6].source, "=5=");
+        assert_eq!(listing.lines[7].location, Some(104));
+        assert_eq!(listing.lines[8].number, 5);
+        assert_eq!(listing.lines[8].source, "SUM     CON  0");
+
+        let text = listing.to_string();
+        assert!(text.contains("   3  0100: + 0103 00 05 08   START   LDA  =5="));
+        assert!(text.contains("START      100"));
+    }
+
+    #[test]
+    fn test_disassemble() {
+        for instruction in [
+            "LDA 2000,2(1:3)",
+            "LD6N -1",
+            "STJ 100",
+            "STA 100(0:2)",
+            "MOVE 1000(3)",
+            "OUT 1000(18)",
+            "JXNZ 3000,1",
+            "ENN5 0",
+            "CMP1 5(4:4)",
+        ] {
+            let program = assembler::assemble(&format!(" {}\n END 0", instruction)).unwrap();
+            let word = program.computer.memory[0];
+            assert_eq!(disassembler::disassemble(word).unwrap(), instruction);
+            assert_eq!(word.to_string(), instruction);
+        }
+
+        let not_instructions: [MIXWord; 4] = [
+            (0, 0, 0, 0, 3, 5).into(),
+            (0, 0, 0, 0, 8, 6).into(),
+            (1, 0, 10, 0, 6, 8).into(),
+            (0, 0, 0, 7, 5, 8).into(),
+        ];
+        for word in not_instructions {
+            assert!(disassembler::disassemble(word).is_err());
+            assert!(word.to_string().starts_with("CON"));
+        }
+        assert_eq!(
+            MIXWord::from((1, 0, 10, 0, 6, 8)).to_string(),
+            "CON -2621832"
+        );
+
+        // the REPL and the assembler read back every mnemonic the disassembler writes.
+        for c in 0..64 {
+            for f in 0..64 {
+                if let Some(name) = disassembler::mnemonic(c, f) {
+                    assert_eq!(command_parser::operation(&name).map(|(c, _)| c), Some(c));
+                    let word: MIXWord = format!("{} 0", name).as_str().try_into().unwrap();
+                    assert_eq!(word.get_op(), c);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_charset() {
+        let text: String = charset::CHARS.iter().collect();
+        let words = charset::to_words(&text, charset::Unmappable::Error).unwrap();
+        assert_eq!(words.len(), 12);
+        assert_eq!(words[6], (0, 30, 31, 32, 33, 34).into());
+        assert_eq!(words[11], (0, 55, 0, 0, 0, 0).into());
+        let back = charset::from_words(&words, charset::Unmappable::Error).unwrap();
+        assert_eq!(back.trim_end(), text.trim_end());
+        assert_eq!(back.chars().count(), 60);
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        assert!(assembler::assemble(" LDA X+1\n END 0").is_err());
+        assert!(assembler::assemble("X EQU 1\nX EQU 2\n END 0").is_err());
+        assert!(assembler::assemble(" LDA 0").is_err());
+        assert!(assembler::assemble(" FOO 0\n END 0").is_err());
+    }
+
+    #[test]
+    fn test_byte_size() {
+        use mixword::ByteSize;
+        use unit::MemoryDevice;
+        let source = "\
+* 12 factorial, printed and divided, then the size of a byte.
+        ORIG 100
+START   ENTA 1
+        ENT1 12
+LOOP    ST1  N
+        MUL  N
+        SLAX 5
+        DEC1 1
+        J1P  LOOP
+        STA  FACT
+        CHAR
+        STA  LINE
+        STX  LINE+1
+        OUT  LINE(18)
+        ENTA 0
+        LDX  FACT
+        DIV  =1000=
+        STA  QUOT
+        STX  REM
+        ENTA 1
+        SLA  1
+        STA  BYTE
+        LDA  BIG
+        INCA 1
+        HLT
+N       CON  0
+FACT    CON  0
+QUOT    CON  0
+REM     CON  0
+BYTE    CON  0
+BIG     CON  1073741823
+LINE    ORIG *+24
+        END  START";
+        for size in [ByteSize::Binary, ByteSize::Decimal] {
+            let program = assembler::assemble_for(source, size).unwrap();
+            let symbols = program.symbols().clone();
+            let mut computer = MIXCPU::from(program.computer);
+            computer.computer.units[18].attach(Box::new(MemoryDevice::new(24)));
+            computer.location = program.start as i64;
+            computer.start();
+
+            let value = |name: &str| computer.computer.memory[symbols[name] as usize].get_value();
+            assert_eq!(value("FACT"), 479001600, "{:?}", size);
+            assert_eq!((value("QUOT"), value("REM")), (479001, 600), "{:?}", size);
+            // the assumptions a program must not make.
+            assert_eq!(value("BYTE"), size.base() as i64, "{:?}", size);
+            let wide = size == ByteSize::Decimal;
+            assert_eq!(computer.computer.overflow, !wide, "{:?}", size);
+            assert_eq!(
+                computer.computer.register[0].get_value(),
+                if wide { 1 << 30 } else { 0 }
+            );
+            let snapshot = serde_json::to_string(&computer.computer).unwrap();
+            let restored: MIXComputer = serde_json::from_str(&snapshot).unwrap();
+            assert_eq!(restored.memory, computer.computer.memory);
+            assert_eq!(restored.register, computer.computer.register);
+            assert_eq!(restored.byte_size, size);
+            let printer = computer.computer.units[18]
+                .device::<MemoryDevice>()
+                .unwrap();
+            printer.assert_lines(&["0479001600"]);
+        }
+
+        // F and AA keep their decimal range once an instruction is fetched.
+        let program = assembler::assemble_for(
+            " ENT1 3000\n MOVE 2000(70)\n ENT2 5000\n HLT\n END 0",
+            ByteSize::Decimal,
+        )
+        .unwrap();
+        let mut computer = MIXCPU::from(program.computer);
+        computer.computer.memory[2069] = MIXWord::from_value(42);
+        computer.start();
+        assert!(computer.halted());
+        assert_eq!(computer.computer.memory[3069].get_value(), 42);
+        assert_eq!(computer.computer.register[1].get_value(), 3070);
+        assert_eq!(computer.computer.register[2].get_value(), 5000);
+        // so do instructions typed at the REPL, which read them with the machine's bytes.
+        computer.run_command("ENT3 6000").unwrap();
+        assert_eq!(computer.computer.register[3].get_value(), 6000);
+        computer.computer.byte_size = ByteSize::Binary;
+        assert!(computer.run_command("ENT3 6000").is_err());
+    }
+
+    #[test]
+    fn test_registers() {
+        let mut computer = MIXCPU::from(MIXComputer::new());
+        computer.computer.memory[1000] = MIXWord::from_value(-70000);
+        computer.run_command("LD1 1000(4:5)").unwrap();
+        assert_eq!(
+            computer.computer.register.i(1).unwrap().get_value(),
+            70000 % 4096
+        );
+        // an index register holds two bytes; more would be undefined.
+        assert!(computer.run_command("LD2 1000").is_err());
+        assert_eq!(computer.computer.register.i(2).unwrap().get_value(), 0);
+        computer.run_command("ENT3 4095").unwrap();
+        assert!(computer.run_command("INC3 1").is_err());
+        assert_eq!(computer.computer.register.i(3).unwrap().get_value(), 4095);
+        computer.run_command("DEC3 4095").unwrap();
+        computer.run_command("DEC3 4095").unwrap();
+        assert_eq!(computer.computer.register[3].get_value(), -4095);
+        assert!(!computer.computer.overflow);
+        // rA is as wide as a word.
+        computer.run_command("LDA 1000").unwrap();
+        assert_eq!(computer.computer.register.a().get_value(), -70000);
+
+        let snapshot = serde_json::to_value(&computer.computer).unwrap();
+        assert_eq!(snapshot["register"].as_array().unwrap().len(), 9);
+    }
+
+    #[test]
+    fn test_errors() {
+        use unit::MemoryDevice;
+        let mut computer = MIXCPU::from(MIXComputer::new());
+        for unit in computer.computer.units.iter_mut() {
+            let size = unit.get_block_size().unwrap() as usize;
+            unit.attach(Box::new(MemoryDevice::new(size)));
+        }
+        let mut fault = |command: &str| computer.run_command(command).unwrap_err().fault().clone();
+        assert_eq!(fault("LDA 4000"), MixError::Address(4000));
+        assert_eq!(fault("DIV 0"), MixError::DivideByZero);
+        assert!(matches!(
+            fault("LDA 0(7:7)"),
+            MixError::InvalidInstruction(_)
+        ));
+        assert!(matches!(
+            fault("IN 0(21)"),
+            MixError::Device { unit: 21, .. }
+        ));
+        assert!(matches!(
+            fault("IN 0(16)"),
+            MixError::Device { unit: 16, .. }
+        ));
+        assert!(matches!(fault("INT 0"), MixError::Undefined(_)));
+        assert!(matches!(
+            computer.run_command("LDQ 0"),
+            Err(MixError::Parse(_))
+        ));
+        assert!(matches!(
+            computer.run_command("LDA 0,"),
+            Err(MixError::Parse(_))
+        ));
+
+        // a fault in a program tells where it happened.
+        computer.location = 3000;
+        computer.computer.memory[3000] = "JMP 4000".try_into().unwrap();
+        let error = computer.execute_in_location().unwrap_err();
+        assert_eq!(error.location(), Some(3000));
+        assert_eq!(error.instruction(), Some(computer.computer.memory[3000]));
+        assert_eq!(error.fault(), &MixError::Address(4000));
+        assert_eq!(computer.location, 3001);
+        assert_eq!(
+            computer.run_command("LDA 0,9").unwrap_err().fault(),
+            &MixError::InvalidInstruction("LDA 0,9".try_into().unwrap())
+        );
+
+        // no instruction panics, whatever its fields and the byte size.
+        for size in [mixword::ByteSize::Binary, mixword::ByteSize::Decimal] {
+            computer.computer.byte_size = size;
+            for c in 0..64 {
+                for f in 0..64 {
+                    for i in 0..64 {
+                        for m in [-1i64, 1000, 3999] {
+                            let (a, b) = (m.unsigned_abs() as u32, size.base() as u32);
+                            let bytes = [(m < 0) as u32, a / b, a % b, i, f, c];
+                            let ins = MIXWord::from_bytes(bytes, size);
+                            let _ = computer.execute_instruction(ins);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        use mixcpu::RunOutcome;
+        let mut computer = MIXCPU::from(MIXComputer::new());
+        let program = [
+            "ENT1 0",
+            "INC1 1",
+            "CMP1 3000",
+            "JL 1",
+            "HLT 0",
+            "JMP 5",
+            "LDA 4000",
+        ];
+        for (i, ins) in program.iter().enumerate() {
+            computer.computer.memory[i] = (*ins).try_into().unwrap();
+        }
+        computer.computer.memory[3000] = MIXWord::from_value(3);
+
+        // one step at a time.
+        computer.step().unwrap();
+        computer.step().unwrap();
+        assert_eq!(computer.location, 2);
+        assert_eq!(computer.computer.register.i(1).unwrap().get_value(), 1);
+
+        // a breakpoint stops before its instruction, and running again passes it.
+        computer
+            .breakpoints
+            .insert(1, breakpoint::Breakpoint::new());
+        assert_eq!(computer.run(), RunOutcome::Breakpoint(1));
+        assert_eq!(computer.computer.register.i(1).unwrap().get_value(), 1);
+        computer.breakpoints.clear();
+        assert_eq!(computer.run(), RunOutcome::Halted);
+        assert!(computer.halted());
+        assert_eq!(computer.location, 5);
+
+        // JMP 5 loops forever, so only the budget stops it.
+        assert_eq!(computer.run_for(10), RunOutcome::StepLimit);
+        computer.budget = Some(1000);
+        assert_eq!(computer.run(), RunOutcome::StepLimit);
+        computer.budget = None;
+        computer.location = 0;
+        assert_eq!(
+            computer.run_until(|cpu| cpu.computer.register.i(1).unwrap().get_value() == 2),
+            RunOutcome::Breakpoint(2)
+        );
+
+        // a fault stops at the faulting instruction.
+        computer.location = 6;
+        let RunOutcome::Fault { location, error } = computer.run() else {
+            panic!("LDA 4000 should fault");
+        };
+        assert_eq!(location, 6);
+        assert_eq!(error.fault(), &MixError::Address(4000));
+        assert_eq!(computer.location, 6);
+    }
+
+    #[test]
+    fn test_step_over() {
+        use mixcpu::RunOutcome;
+        let program = assembler::assemble(
+            "        ORIG 3000
+START   JMP  SUB
+        JMP  LATE
+        JMP  SUB
+        HLT
+SUB     STJ  9F
+        INC1 1
+9H      JMP  *
+LATE    INC1 10
+        STJ  8F
+8H      JMP  *
+        END  START",
+        )
+        .unwrap();
+        let mut computer = MIXCPU::from(program.computer);
+        computer.location = program.start as i64;
+
+        assert_eq!(computer.step_over(), RunOutcome::StepLimit);
+        assert_eq!(computer.location, 3001);
+        assert_eq!(computer.computer.register.i(1).unwrap().get_value(), 1);
+        // a subroutine that saves rJ late is stepped over all the same.
+        assert_eq!(computer.step_over(), RunOutcome::StepLimit);
+        assert_eq!(computer.location, 3002);
+        assert_eq!(computer.computer.register.i(1).unwrap().get_value(), 11);
+
+        // a breakpoint inside the subroutine still stops it.
+        computer
+            .breakpoints
+            .insert(3005, breakpoint::Breakpoint::new());
+        assert_eq!(computer.step_over(), RunOutcome::Breakpoint(3005));
+        assert_eq!(computer.step_over(), RunOutcome::StepLimit);
+        assert_eq!(computer.location, 3006);
+        // the return is a JMP too, so stepping over it runs on.
+        assert_eq!(computer.step_over(), RunOutcome::Halted);
+        assert_eq!(computer.location, 3004);
+        assert_eq!(computer.computer.register.i(1).unwrap().get_value(), 12);
+    }
+
+    #[test]
+    fn test_watchpoints() {
+        use mixcpu::RunOutcome;
+        use std::cmp::Ordering;
+        use watch::{Access, Value, Watch, Watchpoint};
+        let program = assembler::assemble(
+            "        ORIG 3000
+START   ENTA 5
+        STA  2000
+        STA  2000
+        LDA  2000
+        ADD  BIG
+        CMPA =1=
+        ENT1 2010
+        MOVE 2000(2)
+        HLT
+BIG     CON  1073741823
+        END  START",
+        )
+        .unwrap();
+        let mut computer = MIXCPU::from(program.computer);
+        computer.location = program.start as i64;
+        computer.watchpoints = vec![
+            Watchpoint::new(Watch::Memory(2000..=2000)),
+            Watchpoint::new(Watch::Register(0)),
+        ];
+        let mut hits = || match computer.run() {
+            RunOutcome::Watchpoint(hits) => hits,
+            outcome => panic!("{:?}", outcome),
+        };
+
+        let hit = &hits()[0];
+        assert_eq!(hit.watch, Watch::Register(0));
+        assert_eq!((hit.location, hit.new), (3000, Value::Word(5.into())));
+        // a store stops even when the word keeps its value.
+        assert_eq!(hits()[0].location, 3001);
+        let hit = &hits()[0];
+        assert_eq!(hit.location, 3002);
+        assert_eq!(hit.old, hit.new);
+        // LDA gives rA the value it had, and the sum overflows.
+        let hit = &hits()[0];
+        assert_eq!(hit.location, 3004);
+        assert_eq!(hit.access, Access::Write);
+        assert_eq!(computer.run(), RunOutcome::Halted);
+
+        // reads, the indicators, rI1 and the words MOVE writes.
+        computer.location = program.start as i64;
+        computer.computer.overflow = false;
+        computer.computer.comp = Ordering::Equal;
+        computer.computer.memory[2010] = MIXWord::from_value(0);
+        computer.watchpoints = vec![
+            Watchpoint::new(Watch::Memory(2000..=2000)).reads(),
+            Watchpoint::new(Watch::Overflow),
+            Watchpoint::new(Watch::Comparison),
+            Watchpoint::new(Watch::Memory(2010..=2011)),
+        ];
+        let mut stops = Vec::new();
+        loop {
+            match computer.run() {
+                RunOutcome::Watchpoint(hits) => stops.push(hits),
+                RunOutcome::Halted => break,
+                outcome => panic!("{:?}", outcome),
+            }
+        }
+        let locations: Vec<i64> = stops.iter().map(|hits| hits[0].location).collect();
+        assert_eq!(locations, [3001, 3002, 3003, 3004, 3005, 3007]);
+        assert_eq!(stops[2][0].access, Access::Read);
+        assert_eq!(stops[3][0].new, Value::Toggle(true));
+        assert_eq!(stops[4][0].watch, Watch::Comparison);
+        assert_eq!(stops[4][0].new, Value::Indicator(Ordering::Greater));
+        // MOVE reads 2000 and writes 2010 and 2011.
+        let watches: Vec<String> = stops[5].iter().map(|hit| hit.to_string()).collect();
+        assert_eq!(
+            watches,
+            [
+                "2000 read at location 3007: 5",
+                "2010 written at location 3007: 0 -> 5",
+                "2011 written at location 3007: 0 -> 0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_conditions() {
+        use breakpoint::{Breakpoint, Tracepoint};
+        use expression::Expression;
+        use mixcpu::RunOutcome;
+        let mut computer = MIXCPU::from(MIXComputer::new());
+        let value = |source: &str, computer: &MIXCPU| {
+            source.parse::<Expression>().unwrap().evaluate(computer)
+        };
+        computer.run_command("ENTA 300").unwrap();
+        computer.run_command("ENT2 -2").unwrap();
+        computer.computer.memory[2000] = (1, 0, 0, 5, 9, 7).into();
+        assert_eq!(value("rA > 100", &computer), Ok(1));
+        assert_eq!(value("mem[2000](1:3) == 5", &computer), Ok(1));
+        assert_eq!(value("mem[2000](0:3)", &computer), Ok(-5));
+        assert_eq!(value("mem[2000 + rI2](4:4)", &computer), Ok(0));
+        assert_eq!(value("rI2 == 0 && OV", &computer), Ok(0));
+        assert_eq!(value("1 + 2 * 3 - -4 == 11 || mem[-1]", &computer), Ok(1));
+        assert_eq!(
+            value("rA(4:5) / (1 - 1)", &computer),
+            Err(MixError::DivideByZero)
+        );
+        assert_eq!(value("mem[rI2]", &computer), Err(MixError::Address(-2)));
+        for invalid in ["", "rA >", "rI7", "mem[1](5:4)", "(rA", "rA 1", "rA = 1"] {
+            assert!(invalid.parse::<Expression>().is_err(), "{}", invalid);
+        }
+
+        // a loop running a thousand times, stopped and traced only when it matters.
+        let program = assembler::assemble(
+            "        ORIG 3000
+START   ENT1 0
+LOOP    INC1 1
+        CMP1 =1000=
+        JL   LOOP
+        HLT
+        END  START",
+        )
+        .unwrap();
+        let mut computer = MIXCPU::from(program.computer);
+        computer.location = program.start as i64;
+        let output = unit::SharedBuffer::default();
+        computer.trace_output = Box::new(output.clone());
+        let condition = "rI1 % 400 == 0".parse().unwrap();
+        let tracepoint = Tracepoint::new("rI1 = {rI1}, CI {CI}").unwrap();
+        computer
+            .tracepoints
+            .insert(3002, tracepoint.when(condition));
+        let breakpoint = Breakpoint::when("rI1 == 500".parse().unwrap());
+        computer.breakpoints.insert(3001, breakpoint);
+        assert_eq!(computer.run(), RunOutcome::Breakpoint(3001));
+        assert_eq!(computer.computer.register.i(1).unwrap().get_value(), 500);
+        assert_eq!(computer.run(), RunOutcome::Halted);
+        assert_eq!(
+            output.contents(),
+            "3002: rI1 = 400, CI -1\n3002: rI1 = 800, CI -1\n"
+        );
+
+        // trace output that cannot be written stops the run at the tracepoint.
+        struct Closed;
+        impl std::io::Write for Closed {
+            fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        computer.trace_output = Box::new(Closed);
+        computer.breakpoints.clear();
+        computer.location = program.start as i64;
+        assert!(matches!(
+            computer.run(),
+            RunOutcome::Fault {
+                location: 3002,
+                error: MixError::Io(_)
+            }
+        ));
+    }
+
+    #[test]
+    fn test_step_back() {
+        use breakpoint::Breakpoint;
+        use mixcpu::RunOutcome;
+        let program = assembler::assemble(
+            "\
+START      ENTA 7
+           ENT1 1000
+           INT
+           MOVE 2000(2)
+           HLT
+* INT HANDLER, RUN IN CONTROL STATE
+           ORIG 3000
+           ENTA 42
+           STA  2000
+           INT
+           END  START
+",
+        )
+        .unwrap();
+        let mut computer = MIXCPU::from(program.computer);
+        computer.enable_interrupts();
+        let interrupts = computer.interrupts.as_mut().unwrap();
+        *interrupts.get_mut(interrupt::INT_HANDLER).unwrap() = "JMP 3000".try_into().unwrap();
+        computer.location = program.start as i64;
+        let state = |computer: &MIXCPU| {
+            let interrupts = computer.interrupts.as_ref().unwrap();
+            let control: Vec<MIXWord> = (-12..0).map(|l| *interrupts.get(l).unwrap()).collect();
+            (
+                computer.computer.memory.to_vec(),
+                computer.computer.register,
+                control,
+                interrupts.control,
+                computer.location,
+                computer.time,
+            )
+        };
+        let start = state(&computer);
+        assert_eq!(computer.run(), RunOutcome::Halted);
+        let end = state(&computer);
+        assert_eq!(computer.computer.memory[1000], MIXWord::from_value(42));
+
+        // back into the handler, before it stores rA.
+        computer.breakpoints.insert(3001, Breakpoint::new());
+        assert_eq!(computer.run_back(), RunOutcome::Breakpoint(3001));
+        assert!(computer.interrupts.as_ref().unwrap().control);
+        assert_eq!(computer.computer.register.a().get_value(), 42);
+        assert_eq!(computer.computer.memory[2000], MIXWord::from_value(0));
+        assert!(!computer.halted());
+
+        // back to the start, and forward again to the same end.
+        computer.breakpoints.clear();
+        assert_eq!(computer.run_back(), RunOutcome::StepLimit);
+        assert!(state(&computer) == start);
+        assert_eq!(computer.run(), RunOutcome::Halted);
+        assert!(state(&computer) == end);
+
+        // a bounded journal forgets the oldest instructions.
+        computer.journal.set_capacity(2);
+        assert_eq!(computer.journal.len(), 2);
+        assert!(computer.step_back() && computer.step_back() && !computer.step_back());
+        assert_eq!(computer.location, 3);
+        assert_eq!(computer.computer.memory[1000], MIXWord::from_value(0));
+
+        // the words a faulting MOVE wrote before running off memory are undone too.
+        let mut computer = MIXCPU::from(MIXComputer::new());
+        computer.computer.memory[0] = "ENT1 3998".try_into().unwrap();
+        computer.computer.memory[1] = "MOVE 100(5)".try_into().unwrap();
+        computer.computer.memory[100] = MIXWord::from_value(7);
+        assert!(matches!(
+            computer.run(),
+            RunOutcome::Fault { location: 1, .. }
+        ));
+        assert_eq!(computer.computer.memory[3998], MIXWord::from_value(7));
+        assert!(computer.step_back());
+        assert_eq!(computer.computer.memory[3998], MIXWord::from_value(0));
+        assert_eq!(computer.computer.register.i(1).unwrap().get_value(), 3998);
+        assert_eq!(computer.location, 1);
+
+        // the saved state INT wrote is dropped if interrupts are disabled since.
+        let mut computer = MIXCPU::from(MIXComputer::new());
+        computer.enable_interrupts();
+        computer.computer.memory[0] = "INT 0".try_into().unwrap();
+        computer.step().unwrap();
+        computer.interrupts = None;
+        assert!(computer.step_back());
+        assert_eq!(computer.location, 0);
+
+        // with no journal, nothing is kept.
+        let mut computer = MIXCPU::from(MIXComputer::new());
+        computer.journal = journal::Journal::new(0);
+        computer.step().unwrap();
+        assert!(computer.journal.is_empty());
+        assert!(!computer.step_back());
     }
 }
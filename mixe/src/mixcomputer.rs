@@ -1,10 +1,11 @@
-use crate::mixword::MIXWord;
-use crate::Unit;
-use std::cmp::Ordering;
+use crate::mixword::{ByteSize, MIXWord};
+use crate::registers::Registers;
+use crate::unit::{Unit, UNIT_COUNT};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 
 mod ordering_serde {
-    use serde::{Serializer, Deserializer, Deserialize};
+    use serde::{Deserialize, Deserializer, Serializer};
     use std::cmp::Ordering;
 
     pub fn serialize<S>(ordering: &Ordering, serializer: S) -> Result<S::Ok, S::Error>
@@ -36,15 +37,18 @@ mod ordering_serde {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct MIXComputer {
     // A J1 J2 J3 J4 J5 J6 X J
-    #[serde(with = "serde_arrays")]
-    pub register: [MIXWord; 9],
+    pub register: Registers,
     pub overflow: bool,
     #[serde(with = "ordering_serde")]
     pub comp: Ordering, // -1 0 1
-    #[serde(with = "serde_arrays")]
-    pub units: [Unit; 16],
+    // devices hold open files and are not part of a snapshot.
+    #[serde(skip, default = "units")]
+    pub units: [Unit; UNIT_COUNT],
     #[serde(with = "serde_arrays")]
     pub memory: [MIXWord; 4000],
+    /// binary or decimal bytes; snapshots from before it was configurable are binary.
+    #[serde(default)]
+    pub byte_size: ByteSize,
 }
 
 impl Default for MIXComputer {
@@ -53,14 +57,36 @@ impl Default for MIXComputer {
     }
 }
 
+fn units() -> [Unit; UNIT_COUNT] {
+    std::array::from_fn(|i| Unit::new(i as u32))
+}
+
 impl MIXComputer {
     pub fn new() -> Self {
         MIXComputer {
-            register: [0u32.into(); 9],
+            register: Registers::default(),
             overflow: false,
             comp: Ordering::Less,
-            units: std::array::from_fn(|i| Unit::new(i as u32)),
+            units: units(),
             memory: [0u32.into(); 4000],
+            byte_size: ByteSize::Binary,
+        }
+    }
+
+    /// ### With byte size
+    /// an empty machine whose bytes hold `byte_size.base()` values.
+    /// ```rust
+    /// use mixe::mixword::ByteSize;
+    /// use mixe::{MIXComputer, MIXCPU};
+    /// let mut computer = MIXCPU::from(MIXComputer::with_byte_size(ByteSize::Decimal));
+    /// computer.run_command("ENTA 99").unwrap();
+    /// computer.run_command("SLA 1").unwrap();
+    /// assert_eq!(computer.computer.register[0].get_value(), 9900);
+    /// ```
+    pub fn with_byte_size(byte_size: ByteSize) -> Self {
+        MIXComputer {
+            byte_size,
+            ..Self::new()
         }
     }
 }
@@ -1,54 +1,585 @@
-use super::mixword::MASK;
+use crate::breakpoint::{Breakpoint, Tracepoint};
+use crate::command_parser::parse_for;
+use crate::error::MixError;
+use crate::float;
+use crate::interrupt::{Interrupts, CONTROL_MEMORY, INT_HANDLER, SAVED_STATE, TIMER};
+use crate::journal::{Entry, Journal};
 use crate::mixcomputer::MIXComputer;
-use crate::mixword::MIXWord;
+use crate::mixword::{ByteSize, MIXWord};
+use crate::registers::Registers;
+use crate::watch::{Access, Hit, Value, Watch, Watchpoint};
 use std::cmp::Ordering;
-use std::error::Error;
+use std::collections::BTreeMap;
+use std::io::Write;
 use std::ops::RangeInclusive;
 
 pub struct MIXCPU {
-    pub location: usize,
+    /// negative only in control state.
+    pub location: i64,
     running: bool,
     pub computer: MIXComputer,
+    /// time elapsed in units of `u`, see [`execution_time`].
+    pub time: u64,
+    /// the interrupt facility, when enabled with [`MIXCPU::enable_interrupts`].
+    pub interrupts: Option<Interrupts>,
+    /// the most instructions one run may execute, `None` for no limit.
+    pub budget: Option<u64>,
+    /// locations a run stops at before executing them.
+    pub breakpoints: BTreeMap<i64, Breakpoint>,
+    pub tracepoints: BTreeMap<i64, Tracepoint>,
+    /// where tracepoints write, standard output by default.
+    pub trace_output: Box<dyn Write>,
+    pub watchpoints: Vec<Watchpoint>,
+    hits: Vec<Hit>,
+    /// what the last instructions changed, to step back through them.
+    pub journal: Journal,
+    // the words the current instruction wrote, as they were before.
+    written: Vec<(i64, MIXWord)>,
 }
 
 pub const MEMORY_MAX: usize = 3999;
 pub const MEMORY_RANGE: RangeInclusive<usize> = 0..=MEMORY_MAX;
+pub const DEFAULT_BUDGET: u64 = 10_000_000;
+
+/// ### Run outcome
+/// why a run stopped.
+/// ```rust
+/// use mixe::mixcpu::RunOutcome;
+/// use mixe::{MIXComputer, MIXCPU, MixError};
+/// let mut computer = MIXCPU::from(MIXComputer::new());
+/// assert_eq!(
+///     computer.run(),
+///     RunOutcome::Fault {
+///         location: 4000,
+///         error: MixError::Address(4000),
+///     }
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// after an `HLT`; running again goes on from the next instruction.
+    Halted,
+    /// the instruction at `location` could not be executed, and was not passed; or,
+    /// if the current location is past it, it was but the interrupt after it failed.
+    Fault { location: i64, error: MixError },
+    /// the budget ran out.
+    StepLimit,
+    /// stopped at a breakpoint or because the predicate held, before executing `location`.
+    Breakpoint(i64),
+    /// stopped after an instruction touched what watchpoints look at.
+    Watchpoint(Vec<Hit>),
+}
+
+/// ### Execution time
+/// the time of an instruction in units of `u`, from the table in TAOCP 1.3.1.
+/// ```rust
+/// use mixe::mixcpu::execution_time;
+/// use mixe::MIXWord;
+/// assert_eq!(execution_time((0, 0, 2000, 0, 5, 8).into()), 2); // LDA
+/// assert_eq!(execution_time((0, 0, 2000, 0, 5, 4).into()), 12); // DIV
+/// assert_eq!(execution_time((0, 0, 2000, 0, 3, 7).into()), 7); // MOVE 2000(3)
+/// assert_eq!(execution_time((0, 0, 2000, 0, 6, 56).into()), 4); // FCMP
+/// assert_eq!(execution_time((0, 0, 2000, 0, 6, 57).into()), 2); // CMP1 2000(0:6)
+/// ```
+pub fn execution_time(ins: MIXWord) -> u64 {
+    execution_time_for(ins, ByteSize::Binary)
+}
+
+/// ### Execution time for
+/// the [`execution_time`] of an instruction with bytes of the given size.
+/// ```rust
+/// use mixe::mixcpu::execution_time_for;
+/// use mixe::mixword::ByteSize;
+/// use mixe::MIXWord;
+/// let ins = MIXWord::from_bytes([0, 20, 0, 0, 70, 7], ByteSize::Decimal); // MOVE 2000(70)
+/// assert_eq!(execution_time_for(ins, ByteSize::Decimal), 141);
+/// ```
+pub fn execution_time_for(ins: MIXWord, size: ByteSize) -> u64 {
+    let [_, _, _, _, f, c] = ins.bytes(size);
+    let f = f as u64;
+    match (c, f) {
+        (0, _) => 1,
+        (1 | 2, 6) => 4,
+        (3, 6) => 9,
+        (4, 6) => 11,
+        (1 | 2, _) => 2,
+        (3, _) => 10,
+        (4, _) => 12,
+        (5, 6 | 7) => 3,
+        (5, 9) => 2,
+        (5, _) => 10,
+        (6, _) => 2,
+        (7, _) => 1 + 2 * f,
+        (8..=33, _) => 2,
+        (34..=55, _) => 1,
+        (56, 6) => 4,
+        _ => 2,
+    }
+}
+
+/// the field (L:R) of `ins`, which must have 0 <= L <= R <= 5.
+fn field_spec(ins: Instruction) -> Result<(u32, u32), MixError> {
+    let (left, right) = (ins.get_f() / 8, ins.get_f() % 8);
+    if left <= right && right <= 5 {
+        Ok((left, right))
+    } else {
+        Err(MixError::InvalidInstruction(ins.word))
+    }
+}
+
+/// an instruction `±AA I F C`, its parts read from a word with bytes of the machine's size.
+#[derive(Clone, Copy)]
+struct Instruction {
+    /// the word as it is in memory.
+    word: MIXWord,
+    sign: u32,
+    address: u32,
+    index: u32,
+    field: u32,
+    op: u32,
+}
+
+impl Instruction {
+    fn new(word: MIXWord, size: ByteSize) -> Self {
+        let [sign, a1, a2, index, field, op] = word.bytes(size);
+        Instruction {
+            word,
+            sign,
+            address: a1 * size.base() as u32 + a2,
+            index,
+            field,
+            op,
+        }
+    }
+    fn get_op(&self) -> u32 {
+        self.op
+    }
+    fn get_f(&self) -> u32 {
+        self.field
+    }
+    fn get_i(&self) -> u32 {
+        self.index
+    }
+    /// the address part with its sign.
+    fn get_m(&self) -> i64 {
+        match self.sign {
+            1 => -(self.address as i64),
+            _ => self.address as i64,
+        }
+    }
+}
 
 impl MIXCPU {
     // associate functions.
 
     pub fn from(computer: MIXComputer) -> Self {
         MIXCPU {
-            location: 0usize,
+            location: 0,
             computer,
             running: true,
+            time: 0,
+            interrupts: None,
+            budget: Some(DEFAULT_BUDGET),
+            breakpoints: BTreeMap::new(),
+            tracepoints: BTreeMap::new(),
+            trace_output: Box::new(std::io::stdout()),
+            watchpoints: Vec::new(),
+            hits: Vec::new(),
+            journal: Journal::default(),
+            written: Vec::new(),
         }
     }
 
     // public functions.
 
+    /// run, and print why the run stopped unless the program halted.
     pub fn start(&mut self) {
-        self.running = true;
-        while self.running && self.location < 4000 {
-            if let Err(e) = self.execute_instruction(self.computer.memory[self.location]) {
-                println!("{:?}", e);
+        match self.run() {
+            RunOutcome::Halted => {}
+            RunOutcome::Fault { error, .. } => println!("{}", error),
+            RunOutcome::StepLimit => {
+                println!("stopped after {} instructions", self.budget.unwrap_or(0))
+            }
+            RunOutcome::Breakpoint(location) => println!("breakpoint at {}", location),
+            RunOutcome::Watchpoint(hits) => {
+                for hit in hits {
+                    println!("{}", hit);
+                }
+            }
+        }
+    }
+
+    /// ### Step
+    /// execute exactly one instruction and move past it, then let pending interrupts happen.
+    /// A faulting instruction is not passed. A tracepoint at the location logs first,
+    /// and the journal keeps what the instruction changed, even the words a faulting
+    /// one wrote before it stopped.
+    /// ```rust
+    /// use mixe::{MIXComputer, MIXCPU};
+    /// let mut computer = MIXCPU::from(MIXComputer::new());
+    /// computer.computer.memory[0] = (0, 0, 5, 0, 2, 48).into(); // ENTA 5
+    /// computer.step().unwrap();
+    /// assert_eq!(computer.location, 1);
+    /// assert_eq!(computer.computer.register.a().get_value(), 5);
+    /// ```
+    pub fn step(&mut self) -> Result<(), MixError> {
+        self.written.clear();
+        let entry = (self.journal.capacity() > 0).then(|| Entry {
+            location: self.location,
+            register: self.computer.register,
+            overflow: self.computer.overflow,
+            comp: self.computer.comp,
+            time: self.time,
+            running: self.running,
+            control: self
+                .interrupts
+                .as_ref()
+                .map(|interrupts| interrupts.control),
+            memory: Vec::new(),
+        });
+        let result = self.execute_step();
+        if let Some(entry) = entry {
+            self.journal.push(Entry {
+                memory: std::mem::take(&mut self.written),
+                ..entry
+            });
+        }
+        result
+    }
+
+    /// ### Step back
+    /// undo the last instruction in the journal, or return false if there is none.
+    /// ```rust
+    /// use mixe::{MIXComputer, MIXCPU};
+    /// let mut computer = MIXCPU::from(MIXComputer::new());
+    /// computer.computer.memory[0] = (0, 0, 5, 0, 2, 48).into(); // ENTA 5
+    /// computer.computer.memory[1] = (0, 0, 0, 0, 5, 24).into(); // STA 0
+    /// computer.step().unwrap();
+    /// computer.step().unwrap();
+    /// assert!(computer.step_back());
+    /// assert_eq!(computer.computer.memory[0], (0, 0, 5, 0, 2, 48).into());
+    /// assert!(computer.step_back());
+    /// assert_eq!(computer.computer.register.a().get_value(), 0);
+    /// assert_eq!((computer.location, computer.time), (0, 0));
+    /// ```
+    pub fn step_back(&mut self) -> bool {
+        let Some(entry) = self.journal.pop() else {
+            return false;
+        };
+        for (address, word) in entry.memory.into_iter().rev() {
+            // a control-state word is gone if interrupts were disabled since.
+            let slot = match self.interrupts.as_mut() {
+                Some(interrupts) if address < 0 => interrupts.get_mut(address),
+                _ => usize::try_from(address)
+                    .ok()
+                    .and_then(|address| self.computer.memory.get_mut(address)),
+            };
+            if let Some(slot) = slot {
+                *slot = word;
+            }
+        }
+        if let (Some(interrupts), Some(control)) = (self.interrupts.as_mut(), entry.control) {
+            interrupts.control = control;
+        }
+        self.location = entry.location;
+        self.computer.register = entry.register;
+        self.computer.overflow = entry.overflow;
+        self.computer.comp = entry.comp;
+        self.time = entry.time;
+        self.running = entry.running;
+        self.hits.clear();
+        true
+    }
+
+    /// ### Run back
+    /// step back until a breakpoint whose condition holds is reached, or to the start of
+    /// the journal, which is a [`RunOutcome::StepLimit`].
+    pub fn run_back(&mut self) -> RunOutcome {
+        while self.step_back() {
+            let breakpoint = self.breakpoints.get(&self.location);
+            if breakpoint.is_some_and(|breakpoint| breakpoint.holds(self)) {
+                return RunOutcome::Breakpoint(self.location);
             }
-            self.location += 1;
+        }
+        RunOutcome::StepLimit
+    }
+
+    /// what watchpoints the last step triggered.
+    pub fn hits(&self) -> &[Hit] {
+        &self.hits
+    }
+
+    /// whether the last instruction was an `HLT`.
+    pub fn halted(&self) -> bool {
+        !self.running
+    }
+
+    /// ### Run
+    /// execute from the current location until the program halts or faults, the budget
+    /// runs out or a breakpoint whose condition holds is reached. The breakpoint run
+    /// starts at does not count.
+    pub fn run(&mut self) -> RunOutcome {
+        self.run_until(|_| false)
+    }
+
+    /// run at most `n` instructions, or fewer if the budget is smaller.
+    pub fn run_for(&mut self, n: u64) -> RunOutcome {
+        let limit = self.budget.map_or(n, |budget| budget.min(n));
+        self.run_within(Some(limit), |_| false)
+    }
+
+    /// ### Step over
+    /// step, but run a subroutine called by `JMP` through to its return. Every `JMP`
+    /// is taken for a call, as it leaves rJ pointing just past itself, and the call
+    /// returns when control comes back there. Finishing the step is a
+    /// [`RunOutcome::StepLimit`].
+    /// ```rust
+    /// use mixe::mixcpu::RunOutcome;
+    /// use mixe::{MIXComputer, MIXCPU};
+    /// let mut computer = MIXCPU::from(MIXComputer::new());
+    /// computer.computer.memory[0] = (0, 0, 10, 0, 0, 39).into(); // JMP 10
+    /// computer.computer.memory[10] = (0, 0, 1, 0, 0, 49).into(); // INC1 1
+    /// computer.computer.memory[11] = (0, 0, 12, 0, 2, 32).into(); // STJ 12(0:2)
+    /// computer.computer.memory[12] = (0, 0, 0, 0, 0, 39).into(); // JMP *
+    /// assert_eq!(computer.step_over(), RunOutcome::StepLimit);
+    /// assert_eq!(computer.location, 1);
+    /// assert_eq!(computer.computer.register.i(1).unwrap().get_value(), 1);
+    /// ```
+    pub fn step_over(&mut self) -> RunOutcome {
+        let back = self.location + 1;
+        let ins = self.fetch().and_then(|word| self.decode(word));
+        let call = matches!(ins, Ok(ins) if ins.get_op() == 39 && ins.get_f() == 0);
+        if let Err(error) = self.step() {
+            return self.fault(error);
+        }
+        if self.halted() {
+            return RunOutcome::Halted;
+        }
+        if !self.hits.is_empty() {
+            return RunOutcome::Watchpoint(std::mem::take(&mut self.hits));
+        }
+        if !call || self.location == back || self.computer.register.j().get_value() != back {
+            return RunOutcome::StepLimit;
+        }
+        match self.run_until(|cpu| cpu.location == back) {
+            RunOutcome::Breakpoint(location) if location == back => RunOutcome::StepLimit,
+            outcome => outcome,
+        }
+    }
+
+    /// ### Run until
+    /// run, and also stop as soon as `predicate` holds after an instruction.
+    /// ```rust
+    /// use mixe::mixcpu::RunOutcome;
+    /// use mixe::{MIXComputer, MIXCPU};
+    /// let mut computer = MIXCPU::from(MIXComputer::new());
+    /// computer.computer.memory[0] = (0, 0, 1, 0, 0, 49).into(); // INC1 1
+    /// computer.computer.memory[1] = (0, 0, 0, 0, 0, 39).into(); // JMP 0
+    /// let outcome = computer.run_until(|cpu| cpu.computer.register.i(1).unwrap().get_value() == 3);
+    /// assert_eq!(outcome, RunOutcome::Breakpoint(1));
+    /// computer.budget = Some(100);
+    /// assert_eq!(computer.run(), RunOutcome::StepLimit);
+    /// ```
+    pub fn run_until(&mut self, predicate: impl FnMut(&MIXCPU) -> bool) -> RunOutcome {
+        self.run_within(self.budget, predicate)
+    }
+
+    /// ### Enable interrupts
+    /// turn on the interrupt facility of TAOCP exercise 1.4.4-18, in normal state.
+    pub fn enable_interrupts(&mut self) {
+        self.interrupts = Some(Interrupts::new());
+    }
+
+    /// start counting elapsed time from zero again.
+    pub fn reset_time(&mut self) {
+        self.time = 0;
+        for unit in self.computer.units.iter_mut() {
+            unit.release();
         }
     }
 
     /// to solve a command str mentioned in the Book.
-    pub fn run_command(&mut self, command: &str) -> Result<(), Box<dyn Error>> {
-        match command.try_into() {
+    pub fn run_command(&mut self, command: &str) -> Result<(), MixError> {
+        match parse_for(command, self.computer.byte_size) {
             Ok(ins) => self.execute_instruction(ins),
             Err(e) => Err(e),
         }
     }
 
-    pub fn execute_instruction(&mut self, ins: MIXWord) -> Result<(), Box<dyn Error>> {
-        // println!("ins = {}, op = {} {}", ins, ins.get_op(), self.computer.register[1].0);
+    /// ### Execute instruction
+    /// execute `ins`, a word with bytes of the machine's size like those in memory.
+    pub fn execute_instruction(&mut self, ins: MIXWord) -> Result<(), MixError> {
+        let location = self.location;
+        let result = self.decode(ins).and_then(|decoded| self.execute(decoded));
+        result.map_err(|error| MixError::At {
+            location,
+            instruction: ins,
+            error: Box::new(error),
+        })
+    }
+
+    /// execute the instruction at the current location and move past it, even if it faults.
+    pub fn execute_in_location(&mut self) -> Result<(), MixError> {
+        let result = self.fetch().and_then(|ins| self.execute_instruction(ins));
+        self.location += 1;
+        result
+    }
+
+    /// the instruction at the current location, or an error if the word there is not one.
+    pub fn fetch(&self) -> Result<MIXWord, MixError> {
+        let word = self.word(self.location)?;
+        self.decode(word)
+            .map(|_| word)
+            .map_err(|error| MixError::At {
+                location: self.location,
+                instruction: word,
+                error: Box::new(error),
+            })
+    }
+
+    // private functions.
+    //
+
+    fn run_within(
+        &mut self,
+        limit: Option<u64>,
+        mut predicate: impl FnMut(&MIXCPU) -> bool,
+    ) -> RunOutcome {
+        self.running = true;
+        let mut steps = 0;
+        loop {
+            if !self.running {
+                return RunOutcome::Halted;
+            }
+            if limit.is_some_and(|limit| steps >= limit) {
+                return RunOutcome::StepLimit;
+            }
+            let breakpoint = self.breakpoints.get(&self.location);
+            if steps > 0 && breakpoint.is_some_and(|breakpoint| breakpoint.holds(self)) {
+                return RunOutcome::Breakpoint(self.location);
+            }
+            if let Err(error) = self.step() {
+                return self.fault(error);
+            }
+            steps += 1;
+            if !self.hits.is_empty() {
+                return RunOutcome::Watchpoint(std::mem::take(&mut self.hits));
+            }
+            if self.running && predicate(self) {
+                return RunOutcome::Breakpoint(self.location);
+            }
+        }
+    }
+
+    fn execute_step(&mut self) -> Result<(), MixError> {
+        self.running = true;
+        self.hits.clear();
+        if let Some(line) = self
+            .tracepoints
+            .get(&self.location)
+            .and_then(|tracepoint| tracepoint.message(self))
+        {
+            writeln!(self.trace_output, "{}", line)?;
+        }
+        let before = self.time;
+        let (location, state) = (self.location, self.state());
+        let ins = self.fetch()?;
+        self.execute_instruction(ins)?;
+        self.watch_changes(location, state);
+        self.location += 1;
+        // the instruction is done, but an interrupt it lets in is still its fault.
+        self.poll_interrupts(before).map_err(|error| MixError::At {
+            location,
+            instruction: ins,
+            error: Box::new(error),
+        })
+    }
+
+    /// the fault naming the instruction that caused `error`, which may already be passed.
+    fn fault(&self, error: MixError) -> RunOutcome {
+        RunOutcome::Fault {
+            location: error.location().unwrap_or(self.location),
+            error,
+        }
+    }
+
+    /// the registers and indicators, to tell what an instruction changed.
+    fn state(&self) -> (Registers, bool, Ordering) {
+        (
+            self.computer.register,
+            self.computer.overflow,
+            self.computer.comp,
+        )
+    }
+
+    fn watch_changes(
+        &mut self,
+        location: i64,
+        (register, overflow, comp): (Registers, bool, Ordering),
+    ) {
+        if self.watchpoints.is_empty() {
+            return;
+        }
+        let mut changes = Vec::new();
+        for (r, (old, new)) in register
+            .iter()
+            .zip(self.computer.register.iter())
+            .enumerate()
+        {
+            if old != new {
+                changes.push((Watch::Register(r), Value::Word(*old), Value::Word(*new)));
+            }
+        }
+        if overflow != self.computer.overflow {
+            let new = Value::Toggle(self.computer.overflow);
+            changes.push((Watch::Overflow, Value::Toggle(overflow), new));
+        }
+        if comp != self.computer.comp {
+            let new = Value::Indicator(self.computer.comp);
+            changes.push((Watch::Comparison, Value::Indicator(comp), new));
+        }
+        for (watch, old, new) in changes {
+            if self
+                .watchpoints
+                .iter()
+                .any(|w| w.triggers(&watch, Access::Write))
+            {
+                self.hits.push(Hit {
+                    location,
+                    watch,
+                    access: Access::Write,
+                    old,
+                    new,
+                });
+            }
+        }
+    }
+
+    /// record a watched memory access by the current instruction.
+    fn note(&mut self, address: i64, access: Access, old: MIXWord, new: MIXWord) {
+        let Ok(address) = usize::try_from(address) else {
+            return;
+        };
+        let watch = Watch::Memory(address..=address);
+        if self.watchpoints.iter().any(|w| w.triggers(&watch, access)) {
+            self.hits.push(Hit {
+                location: self.location,
+                watch,
+                access,
+                old: Value::Word(old),
+                new: Value::Word(new),
+            });
+        }
+    }
+
+    fn execute(&mut self, ins: Instruction) -> Result<(), MixError> {
+        // only rI1 to rI6 can index an address.
+        self.time += execution_time_for(ins.word, self.computer.byte_size);
 
         match ins.get_op() {
+            1..=5 | 56 if matches!((ins.get_op(), ins.get_f()), (_, 6) | (5, 7)) => {
+                self.execute_float(ins)
+            }
             8..=23 => self.execute_load(ins),
             24..=33 => self.execute_store(ins),
             1..=4 => self.execute_arithmetic(ins),
@@ -59,242 +590,332 @@ impl MIXCPU {
             7 => self.calculate_move(ins),
             0 => Ok(()), // nop
             5 if ins.get_f() == 2 => self.halt(),
+            5 if ins.get_f() == 9 => self.execute_int(),
             5 => self.calculate_num_char(ins),
-            36 => self.computer.units[ins.get_f() as usize].unit_in(self.calculate_address(ins)?),
-            37 => self.computer.units[ins.get_f() as usize]
-                .unit_out(self.calculate_address(ins)?, &self.computer),
-            35 => Ok(()), // IOC
-            34 => Ok(()),
-            38 => {
-                self.computer.register[8].0 = (self.location + 1) as u32;
-                self.jump_to(self.calculate_bigm(ins))
+            35..=37 => self.execute_io(ins),
+            34 | 38 => {
+                let busy = self
+                    .computer
+                    .units
+                    .get(ins.get_f() as usize)
+                    .ok_or_else(|| MixError::device(ins.get_f() as usize, "no such unit"))?
+                    .busy(self.time);
+                if busy == (ins.get_op() == 34) {
+                    self.jump_and_save(self.calculate_m(ins))
+                } else {
+                    Ok(())
+                }
             }
-            _ => unimplemented!(),
+            _ => Err(MixError::InvalidInstruction(ins.word)),
         }
     }
 
-    pub fn execute_in_location(&mut self) -> Result<(), Box<dyn Error>> {
-        self.location += 1;
-        self.execute_instruction(self.computer.memory[self.location - 1])
+    /// the instruction in `word`, whose fields `±AA I F C` are bytes of the machine's size.
+    fn decode(&self, word: MIXWord) -> Result<Instruction, MixError> {
+        let ins = Instruction::new(word, self.computer.byte_size);
+        // no operation is past 63, and the index registers are rI1 to rI6.
+        if ins.get_op() > 0o77 || ins.get_i() > 6 {
+            return Err(MixError::InvalidInstruction(word));
+        }
+        Ok(ins)
     }
 
-    // private functions.
-    //
-
-    fn halt(&mut self) -> Result<(), Box<dyn Error>> {
+    fn halt(&mut self) -> Result<(), MixError> {
         self.running = false;
         Ok(())
     }
 
-    fn calculate_num_char(&mut self, ins: MIXWord) -> Result<(), Box<dyn Error>> {
-        match ins.get_op() {
+    fn calculate_num_char(&mut self, ins: Instruction) -> Result<(), MixError> {
+        match ins.get_f() {
             0 => {
-                let nums: [u32; 6] = self.computer.register[0].into();
-                let nums2: [u32; 6] = self.computer.register[7].into();
-                let x = nums
+                // rA:rX holds ten decimal digits, each byte taken mod 10.
+                let size = self.computer.byte_size;
+                let a = self.computer.register[0].bytes(size);
+                let x = self.computer.register[7].bytes(size);
+                let value = a
                     .into_iter()
                     .skip(1)
-                    .chain(nums2.into_iter().skip(1))
-                    .rev()
-                    .fold(0, |x, y| x * 10 + y);
-                self.computer.register[0].set_unsigned(x);
+                    .chain(x.into_iter().skip(1))
+                    .fold(0u64, |v, b| v * 10 + (b % 10) as u64);
+                let (word, overflow) = size.wrap(value as i128);
+                if overflow {
+                    self.computer.overflow = true;
+                }
+                let mut a = self.computer.register[0];
+                a.set_unsigned(word.get_unsinged());
+                self.set_register(0, a)?;
             }
             1 => {
-                let mut a: Vec<u32> = vec![self.computer.register[0].get_opposite()];
-                let mut x: Vec<u32> = vec![self.computer.register[7].get_opposite()];
-
-                format!("{:010}", self.computer.register[0].get_unsinged())
+                // the ten digits of |rA| as character codes 30..=39, signs unchanged.
+                let digits: Vec<u32> = format!("{:010}", self.computer.register[0].get_unsinged())
                     .chars()
-                    .map(|x| -> u32 { x.to_digit(10).unwrap() })
-                    .enumerate()
-                    .for_each(|(i, b)| {
-                        if i < 5 {
-                            a.push(b);
-                        } else {
-                            x.push(b);
-                        }
-                    });
-
-                self.computer.register[0] = a.into();
-                self.computer.register[7] = x.into();
+                    .map(|d| 30 + d.to_digit(10).unwrap())
+                    .collect();
+                for (reg, codes) in [(0, &digits[..5]), (7, &digits[5..])] {
+                    let mut word = [self.computer.register[reg].get_opposite(), 0, 0, 0, 0, 0];
+                    word[1..].copy_from_slice(codes);
+                    self.set_register(reg, MIXWord::from_bytes(word, self.computer.byte_size))?;
+                }
             }
-            _ => unreachable!(),
+            _ => return Err(MixError::InvalidInstruction(ins.word)),
+        }
+        Ok(())
+    }
+
+    /// `IOC`, `IN` and `OUT`: wait until the unit is ready, then keep it busy
+    /// for the time its device takes. A device that is busy for reasons of its
+    /// own would be waited on forever, so the operation fails instead. Devices
+    /// deal in binary bytes, which keep their values in memory whatever the byte size.
+    fn execute_io(&mut self, ins: Instruction) -> Result<(), MixError> {
+        let size = self.computer.byte_size;
+        let x = self.computer.register[7].get_value();
+        let address = match ins.get_op() {
+            35 => None,
+            // I/O buffers must be in locations 0 to 3999.
+            _ => Some(
+                usize::try_from(self.calculate_address(ins)?)
+                    .map_err(|_| MixError::Address(self.calculate_m(ins)))?,
+            ),
+        };
+        let m = self.calculate_m(ins);
+        let id = ins.get_f() as usize;
+        let unit = self
+            .computer
+            .units
+            .get_mut(id)
+            .ok_or_else(|| MixError::device(id, "no such unit"))?;
+        self.time = self.time.max(unit.ready_at());
+        if unit.busy(self.time) {
+            return Err(MixError::device(id, "busy"));
+        }
+        let block = unit.get_block_size().unwrap_or(0) as usize;
+        // the block as it was, for watchpoints and the journal.
+        let watched = !self.watchpoints.is_empty() || self.journal.capacity() > 0;
+        let old: Vec<MIXWord> = match address {
+            Some(address) if watched => self
+                .computer
+                .memory
+                .iter()
+                .skip(address)
+                .take(block)
+                .copied()
+                .collect(),
+            _ => Vec::new(),
+        };
+        match (ins.get_op(), address) {
+            (36, Some(address)) => {
+                unit.unit_in(address, &mut self.computer.memory, x)?;
+                if size != ByteSize::Binary {
+                    for word in self.computer.memory.iter_mut().skip(address).take(block) {
+                        *word = word
+                            .convert(ByteSize::Binary, size)
+                            .map_err(|e| MixError::device(id, e))?;
+                    }
+                }
+            }
+            (37, Some(address)) if size != ByteSize::Binary => {
+                let block = unit
+                    .get_block_size()
+                    .ok_or_else(|| MixError::device(id, "no such unit"))?
+                    as usize;
+                let words = self
+                    .computer
+                    .memory
+                    .get(address..address + block)
+                    .ok_or(MixError::Address((address + block - 1) as i64))?
+                    .iter()
+                    .map(|word| word.convert(size, ByteSize::Binary))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| MixError::device(id, e))?;
+                unit.unit_out(0, &words, x)?
+            }
+            (37, Some(address)) => unit.unit_out(address, &self.computer.memory, x)?,
+            _ => unit.control(m, x)?,
+        }
+        unit.occupy(self.time);
+        let ready = unit.ready_at();
+        if let Some(interrupts) = self.interrupts.as_mut() {
+            interrupts.expect(ready, id);
+        }
+        let access = match ins.get_op() {
+            36 => Access::Write,
+            _ => Access::Read,
+        };
+        let start = address.unwrap_or(0);
+        for (address, old) in (start..).zip(old) {
+            self.note(address as i64, access, old, self.computer.memory[address]);
+            if access == Access::Write {
+                self.record(address as i64, old);
+            }
+        }
+        Ok(())
+    }
+
+    /// `FADD`, `FSUB`, `FMUL`, `FDIV`, `FLOT`, `FIX` and `FCMP`, on rA.
+    fn execute_float(&mut self, ins: Instruction) -> Result<(), MixError> {
+        let a = self.computer.register[0];
+        let size = self.computer.byte_size;
+        let (result, overflow) = match ins.get_op() {
+            5 if ins.get_f() == 6 => float::flot(a, size),
+            5 => float::fix(a, size),
+            56 => {
+                let v = self.read(self.calculate_address(ins)?)?;
+                self.computer.comp = float::fcmp(a, v, self.computer.memory[0], size);
+                return Ok(());
+            }
+            op => {
+                let v = self.read(self.calculate_address(ins)?)?;
+                match op {
+                    1 => float::fadd(a, v, size),
+                    2 => float::fsub(a, v, size),
+                    3 => float::fmul(a, v, size),
+                    _ => float::fdiv(a, v, size)?,
+                }
+            }
+        };
+        self.set_register(0, result)?;
+        if overflow {
+            self.computer.overflow = true;
+        }
+        Ok(())
+    }
+
+    /// `INT`: interrupt to location -12 in normal state, or return from an
+    /// interrupt in control state.
+    fn execute_int(&mut self) -> Result<(), MixError> {
+        let interrupts = self
+            .interrupts
+            .as_ref()
+            .ok_or_else(|| MixError::Undefined("INT needs the interrupt facility".into()))?;
+        if interrupts.control {
+            self.resume()?;
+        } else {
+            self.interrupt(INT_HANDLER, self.location + 1)?;
         }
+        self.location -= 1; // cpu will + 1
         Ok(())
     }
 
-    fn calculate_move(&mut self, ins: MIXWord) -> Result<(), Box<dyn Error>> {
-        let mut m = self.calculate_address(ins)?;
-        for _ in 0..ins.get_f() {
-            self.computer.memory[self.computer.register[1].get_value() as usize] =
-                self.computer.memory[m];
-            self.computer.register[1].0 += 1;
-            m += 1;
+    /// take a waiting interrupt after the instruction that started at time `before`.
+    fn poll_interrupts(&mut self, before: u64) -> Result<(), MixError> {
+        let Some(interrupts) = self.interrupts.as_mut() else {
+            return Ok(());
+        };
+        let timer = *interrupts.get(TIMER).unwrap();
+        interrupts.tick(before, self.time, self.computer.byte_size);
+        let handler = interrupts.take();
+        self.record(TIMER, timer);
+        match handler {
+            Some(handler) => self.interrupt(handler, self.location),
+            None => Ok(()),
+        }
+    }
+
+    /// save the registers in locations -9 to -1 and enter control state at `handler`.
+    fn interrupt(&mut self, handler: i64, next: i64) -> Result<(), MixError> {
+        let ci = match self.computer.comp {
+            Ordering::Less => 0,
+            Ordering::Equal => 1,
+            Ordering::Greater => 2,
+        };
+        let size = self.computer.byte_size;
+        let b = size.base();
+        let (next, rj) = (next as u64, self.computer.register[8].get_unsinged());
+        let state = MIXWord::from_bytes(
+            [
+                0,
+                (next / b % b) as u32,
+                (next % b) as u32,
+                8 * self.computer.overflow as u32 + ci,
+                (rj / b % b) as u32,
+                (rj % b) as u32,
+            ],
+            size,
+        );
+        let interrupts = self
+            .interrupts
+            .as_mut()
+            .ok_or_else(|| MixError::Undefined("interrupts are disabled".into()))?;
+        let saved: Vec<(i64, MIXWord)> = (SAVED_STATE - 8..=SAVED_STATE)
+            .map(|location| (location, *interrupts.get(location).unwrap()))
+            .collect();
+        for (r, word) in self.computer.register[..8].iter().enumerate() {
+            *interrupts.get_mut(SAVED_STATE - 8 + r as i64).unwrap() = *word;
+        }
+        *interrupts.get_mut(SAVED_STATE).unwrap() = state;
+        interrupts.control = true;
+        self.location = handler;
+        for (location, old) in saved {
+            self.record(location, old);
         }
         Ok(())
     }
 
-    // fn calculate_jump(&mut self, ins: MIXWord) -> Result<(), Box<dyn Error>> {
-    //     match ins.get_op() {
-    //         39 => match ins.get_f() {
-    //             0 => {
-    //                 self.computer.register[8].0 = (self.location + 1) as u32;
-    //                 self.jump_to(self.calculate_bigm(ins))
-    //             }
-    //             1 => {
-    //                 self.jump_to(self.calculate_bigm(ins))
-    //             }
-    //             2 => {
-    //                 if self.computer.overflow {
-    //                     self.computer.register[8].0 = (self.location + 1) as u32;
-    //                     self.jump_to(self.calculate_bigm(ins))
-    //                 } else {
-    //                     Ok(())
-    //                 }
-    //             }
-    //             3 => {
-    //                 if !self.computer.overflow {
-    //                     self.computer.register[8].0 = (self.location + 1) as u32;
-    //                     self.jump_to(self.calculate_bigm(ins))
-    //                 } else {
-    //                     self.computer.overflow = false;
-    //                     Ok(())
-    //                 }
-    //             }
-    //             4 => {
-    //                 // Jump on less
-    //                 if self.computer.comp == Ordering::Less {
-    //                     self.computer.register[8].0 = (self.location + 1) as u32;
-    //                     self.jump_to(self.calculate_bigm(ins))
-    //                 } else {
-    //                     Ok(())
-    //                 }
-    //             }
-    //             5 => {
-    //                 if self.computer.comp == Ordering::Equal {
-    //                     self.computer.register[8].0 = (self.location + 1) as u32;
-    //                     self.jump_to(self.calculate_bigm(ins))
-    //                 } else {
-    //                     Ok(())
-    //                 }
-    //             }
-    //             6 => {
-    //                 if self.computer.comp == Ordering::Greater {
-    //                     self.computer.register[8].0 = (self.location + 1) as u32;
-    //                     self.jump_to(self.calculate_bigm(ins))
-    //                 } else {
-    //                     Ok(())
-    //                 }
-    //             }
-    //             7 => {
-    //                 if self.computer.comp == Ordering::Greater || self.computer.comp == Ordering::Equal {
-    //                     self.computer.register[8].0 = (self.location + 1) as u32;
-    //                     self.jump_to(self.calculate_bigm(ins))
-    //                 } else {
-    //                     Ok(())
-    //                 }
-    //             }
-    //             8 => {
-    //                 if self.computer.comp == Ordering::Greater || self.computer.comp == Ordering::Less {
-    //                     self.computer.register[8].0 = (self.location + 1) as u32;
-    //                     self.jump_to(self.calculate_bigm(ins))
-    //                 } else {
-    //                     Ok(())
-    //                 }
-    //             }
-    //             9 => {
-    //                 if self.computer.comp == Ordering::Less || self.computer.comp == Ordering::Equal {
-    //                     self.computer.register[8].0 = (self.location + 1) as u32;
-    //                     self.jump_to(self.calculate_bigm(ins))
-    //                 } else {
-    //                     Ok(())
-    //                 }
-    //             }
-    //             _ => unimplemented!()
-    //         }
-    //         40..=47 => {
-    //             let reg_number = (ins.get_op() - 40) as usize;
-    //             let reg = self.computer.register[reg_number];
-    //             match ins.get_f() {
-    //                 0 => {
-    //                     if reg.get_value() < 0 {
-    //                         self.computer.register[8].0 = (self.location + 1) as u32;
-    //                         self.jump_to(self.calculate_bigm(ins))
-    //                     } else {
-    //                         Ok(())
-    //                     }
-    //                 }
-    //                 1 => {
-    //                     if reg.get_value() == 0 {
-    //                         self.computer.register[8].0 = (self.location + 1) as u32;
-    //                         self.jump_to(self.calculate_bigm(ins))
-    //                     } else {
-    //                         Ok(())
-    //                     }
-    //                 }
-    //                 2 => {
-    //                     if reg.get_value() > 0 {
-    //                         self.computer.register[8].0 = (self.location + 1) as u32;
-    //                         self.jump_to(self.calculate_bigm(ins))
-    //                     } else {
-    //                         Ok(())
-    //                     }
-    //                 }
-    //                 3 => {
-    //                     if reg.get_value() >= 0 {
-    //                         self.computer.register[8].0 = (self.location + 1) as u32;
-    //                         self.jump_to(self.calculate_bigm(ins))
-    //                     } else {
-    //                         Ok(())
-    //                     }
-    //                 }
-    //                 4 => {
-    //                     if reg.get_value() != 0 {
-    //                         self.computer.register[8].0 = (self.location + 1) as u32;
-    //                         self.jump_to(self.calculate_bigm(ins))
-    //                     } else {
-    //                         Ok(())
-    //                     }
-    //                 }
-    //                 5 => {
-    //                     if reg.get_value() <= 0 {
-    //                         self.computer.register[8].0 = (self.location + 1) as u32;
-    //                         self.jump_to(self.calculate_bigm(ins))
-    //                     } else {
-    //                         Ok(())
-    //                     }
-    //                 }
-    //                 _ => unimplemented!()
-    //             }
-    //         }
-    //         _ => unreachable!()
-    //     }
-    // }
-
-    fn calculate_jump(&mut self, ins: MIXWord) -> Result<(), Box<dyn Error>> {
+    /// restore the registers saved by [`MIXCPU::interrupt`] and return to normal state.
+    fn resume(&mut self) -> Result<(), MixError> {
+        let interrupts = self
+            .interrupts
+            .as_mut()
+            .ok_or_else(|| MixError::Undefined("interrupts are disabled".into()))?;
+        let size = self.computer.byte_size;
+        // the saved words may have been changed since, so all of them are checked
+        // before any register is restored.
+        let mut registers = self.computer.register;
+        for r in 0..8 {
+            let word = *interrupts.get(SAVED_STATE - 8 + r as i64).unwrap();
+            registers.set(r, word, size)?;
+        }
+        let b = size.base() as u32;
+        let [_, n1, n2, state, j1, j2] = interrupts.get(SAVED_STATE).unwrap().bytes(size);
+        registers.set(8, MIXWord::from_value((j1 * b + j2) as i64), size)?;
+        self.computer.register = registers;
+        self.computer.overflow = state & 8 != 0;
+        self.computer.comp = match state & 7 {
+            0 => Ordering::Less,
+            1 => Ordering::Equal,
+            _ => Ordering::Greater,
+        };
+        interrupts.control = false;
+        self.location = (n1 * b + n2) as i64;
+        Ok(())
+    }
+
+    fn calculate_move(&mut self, ins: Instruction) -> Result<(), MixError> {
+        let m = self.calculate_address(ins)?;
+        for offset in 0..ins.get_f() as i64 {
+            let word = self.read(m + offset)?;
+            let destination = self.computer.register[1].get_value();
+            self.write(destination, word)?;
+            self.computer.register.set(
+                1,
+                MIXWord::from_value(destination + 1),
+                self.computer.byte_size,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn calculate_jump(&mut self, ins: Instruction) -> Result<(), MixError> {
         let op = ins.get_op();
         let f = ins.get_f();
 
-
         if op == 39 && f == 1 {
-            return self.jump_to(self.calculate_bigm(ins));
+            return self.jump_to(self.calculate_m(ins));
         }
 
         let jump = match op {
-            39 => {
-                match f {
-                    0 => true,
-                    2 => self.computer.overflow,
-                    3 => !self.computer.overflow,
-                    4 => self.computer.comp == Ordering::Less,
-                    5 => self.computer.comp == Ordering::Equal,
-                    6 => self.computer.comp == Ordering::Greater,
-                    7 => matches!(self.computer.comp, Ordering::Greater | Ordering::Equal),
-                    8 => matches!(self.computer.comp, Ordering::Greater | Ordering::Less),
-                    9 => matches!(self.computer.comp, Ordering::Less | Ordering::Equal),
-                    _ => false,
-                }
-            }
+            39 => match f {
+                0 => true,
+                2 => self.computer.overflow,
+                3 => !self.computer.overflow,
+                4 => self.computer.comp == Ordering::Less,
+                5 => self.computer.comp == Ordering::Equal,
+                6 => self.computer.comp == Ordering::Greater,
+                7 => matches!(self.computer.comp, Ordering::Greater | Ordering::Equal),
+                8 => matches!(self.computer.comp, Ordering::Greater | Ordering::Less),
+                9 => matches!(self.computer.comp, Ordering::Less | Ordering::Equal),
+                _ => false,
+            },
             40..=47 => {
                 let reg_number = (op - 40) as usize;
                 let reg_value = self.computer.register[reg_number].get_value();
@@ -305,268 +926,296 @@ impl MIXCPU {
                     3 => reg_value >= 0,
                     4 => reg_value != 0,
                     5 => reg_value <= 0,
+                    6 => reg_value % 2 == 0,
+                    7 => reg_value % 2 != 0,
                     _ => false,
                 }
             }
-            _ => unreachable!(),
+            _ => return Err(MixError::InvalidInstruction(ins.word)),
         };
 
-        // println!("Jumping to {}, jump = {}", ins, jump);
-        // 特判
+        // JNOV turns the overflow toggle off when it does not jump.
         if op == 39 && f == 3 {
             self.computer.overflow = false;
         }
 
         if jump {
-            self.computer.register[8].0 = (self.location + 1) as u32;
-            self.jump_to(self.calculate_bigm(ins))
+            self.jump_and_save(self.calculate_m(ins))
         } else {
             Ok(())
         }
     }
 
-    fn calculate_miscellaneous(&mut self, ins: MIXWord) -> Result<(), Box<dyn Error>> {
-        fn rotate_left_60_bits(value: u64, k: usize) -> u64 {
-            // 处理移位量，确保在 60 位范围内
-            let k = k % 60;
-
-            // 提取前 4 位和后 60 位
-            let high_bits = value >> 60; // 提取前 4 位
-            let low_bits = value & ((1u64 << 60) - 1); // 提取后 60 位
-
-            // 循环左移后 60 位
-            let rotated_low_bits = (low_bits << k) | (low_bits >> (60 - k));
-
-            // 合并前 4 位和移位后的 60 位
-            (high_bits << 60) | (rotated_low_bits & ((1u64 << 60) - 1))
-        }
-
-        fn rotate_right_60_bits(value: u64, k: usize) -> u64 {
-            // 处理移位量，确保在 60 位范围内
-            let k = k % 60;
-
-            // 提取前 4 位和后 60 位
-            let high_bits = value >> 60; // 提取前 4 位
-            let low_bits = value & ((1u64 << 60) - 1); // 提取后 60 位
-
-            // 循环右移后 60 位
-            let rotated_low_bits = (low_bits >> k) | (low_bits << (60 - k));
-
-            // 合并前 4 位和移位后的 60 位
-            (high_bits << 60) | (rotated_low_bits & ((1u64 << 60) - 1))
-        }
+    /// the shifts: M bytes for `SLA` to `SRC`, M bits for `SLB` and `SRB`; signs are unchanged.
+    fn calculate_miscellaneous(&mut self, ins: Instruction) -> Result<(), MixError> {
+        let size = self.computer.byte_size;
+        let m = u64::try_from(self.calculate_m(ins))
+            .map_err(|_| MixError::Undefined("negative shift amount".into()))?;
+        let a = self.computer.register[0].get_unsinged() as u128;
+        let x = self.computer.register[7].get_unsinged() as u128;
+        let word = size.word_size() as u128;
+        let ax = a * word + x;
+        // b^n, for shifts of n bytes within registers of at most ten bytes.
+        let pow = |n: u64| (size.base() as u128).pow(n.min(10) as u32);
+        // shifting by `n` bytes to the left (or right) within a register of `width` bytes.
+        let shl = |value: u128, n: u64, width: u64| match n < width {
+            true => value % pow(width - n) * pow(n),
+            false => 0,
+        };
+        let shr = |value: u128, n: u64| value / pow(n);
+        let rotate = |value: u128, n: u64| shl(value, n % 10, 10) + shr(value, 10 - n % 10);
 
-        match ins.get_f() {
-            0 => {
-                self.computer.register[0].set_unsigned(
-                    ((self.computer.register[0].get_unsinged() << self.calculate_bigm(ins)) as u32)
-                        & MASK,
-                );
-            }
-            1 => {
-                self.computer.register[0].set_unsigned(
-                    ((self.computer.register[0].get_unsinged() >> self.calculate_bigm(ins)) as u32)
-                        & MASK,
-                );
-            }
-            2 => {
-                let mut ax = (self.computer.register[0].get_unsinged() << 30)
-                    + self.computer.register[7].get_unsinged();
-                ax <<= self.calculate_bigm(ins);
-                ax &= (1u64 << 60) - 1;
-                self.computer.register[0].set_unsigned((ax >> 30) as u32);
-                self.computer.register[0].set_unsigned((ax & ((1 << 30) - 1)) as u32);
-            }
-            3 => {
-                let mut ax = (self.computer.register[0].get_unsinged() << 30)
-                    + self.computer.register[7].get_unsinged();
-                ax >>= self.calculate_bigm(ins);
-                ax &= (1u64 << 60) - 1;
-                self.computer.register[0].set_unsigned((ax >> 30) as u32);
-                self.computer.register[0].set_unsigned((ax & ((1 << 30) - 1)) as u32);
-            }
-            4 => {
-                let mut ax = (self.computer.register[0].get_unsinged() << 30)
-                    + self.computer.register[7].get_unsinged();
-                ax = rotate_left_60_bits(ax, self.calculate_bigm(ins));
-                ax &= (1u64 << 60) - 1;
-                self.computer.register[0].set_unsigned((ax >> 30) as u32);
-                self.computer.register[0].set_unsigned((ax & ((1 << 30) - 1)) as u32);
-            }
-            5 => {
-                let mut ax = (self.computer.register[0].get_unsinged() << 30)
-                    + self.computer.register[7].get_unsinged();
-                ax = rotate_right_60_bits(ax, self.calculate_bigm(ins));
-                ax &= (1u64 << 60) - 1;
-                self.computer.register[0].set_unsigned((ax >> 30) as u32);
-                self.computer.register[0].set_unsigned((ax & ((1 << 30) - 1)) as u32);
+        let (a, x) = match ins.get_f() {
+            0 => (shl(a, m, 5), x),
+            1 => (shr(a, m), x),
+            f => {
+                let ax = match f {
+                    2 => shl(ax, m, 10),
+                    3 => shr(ax, m),
+                    4 => rotate(ax, m),
+                    5 => rotate(ax, 10 - m % 10),
+                    // SLB and SRB need binary bytes.
+                    6 | 7 if size != ByteSize::Binary => {
+                        return Err(MixError::InvalidInstruction(ins.word))
+                    }
+                    6 => ax.checked_shl(m.min(64) as u32).unwrap_or(0) % (word * word),
+                    7 => ax.checked_shr(m.min(64) as u32).unwrap_or(0),
+                    _ => return Err(MixError::InvalidInstruction(ins.word)),
+                };
+                (ax / word, ax % word)
             }
-            _ => unimplemented!(),
-        }
+        };
+        let (mut ra, mut rx) = (self.computer.register[0], self.computer.register[7]);
+        ra.set_unsigned(a as u64);
+        rx.set_unsigned(x as u64);
+        self.set_register(0, ra)?;
+        self.set_register(7, rx)
+    }
+
+    fn jump_to(&mut self, location: i64) -> Result<(), MixError> {
+        self.check_address(location)?;
+        self.location = location - 1; // cpu will + 1
         Ok(())
     }
 
-    fn jump_to(&mut self, location: usize) -> Result<(), Box<dyn Error>> {
-        // println!("jump to {}", location);
-        if MEMORY_RANGE.contains(&location) {
-            self.location = location - 1; // cpu will + 1
-            Ok(())
-        } else {
-            Err("location out of range".into())
-        }
+    /// jump to `location` and save the next location in rJ, leaving rJ alone
+    /// if `location` is out of range.
+    fn jump_and_save(&mut self, location: i64) -> Result<(), MixError> {
+        self.check_address(location)?;
+        self.computer.register.link(self.location + 1);
+        self.jump_to(location)
+    }
+
+    fn set_register(&mut self, r: usize, word: MIXWord) -> Result<(), MixError> {
+        self.computer.register.set(r, word, self.computer.byte_size)
     }
 
-    fn execute_compare(&mut self, ins: MIXWord) -> Result<(), Box<dyn Error>> {
-        let reg_number = (ins.get_op() - 58) as usize;
-        let l = self.computer.register[reg_number];
-        let r = self.computer.memory[self.calculate_address(ins)?];
+    fn execute_compare(&mut self, ins: Instruction) -> Result<(), MixError> {
+        let reg_number = (ins.get_op() - 56) as usize;
+        let (left, right) = field_spec(ins)?;
+        let size = self.computer.byte_size;
+        let l = self.computer.register[reg_number].get_range_for(left, right, size);
+        let r = self
+            .read(self.calculate_address(ins)?)?
+            .get_range_for(left, right, size);
         self.computer.comp = l.get_value().cmp(&r.get_value());
         Ok(())
     }
 
-    fn execute_transfer(&mut self, ins: MIXWord) -> Result<(), Box<dyn Error>> {
-        let v = MIXWord::from_value(self.calculate_bigm(ins) as i64);
+    fn execute_transfer(&mut self, ins: Instruction) -> Result<(), MixError> {
+        let v = MIXWord::from_value(self.calculate_m(ins));
         let reg_number = (ins.get_op() - 48) as usize;
-        match ins.get_f() {
-            0 => self.execute_arithmetic_number(1, v, reg_number, 7),
-            1 => self.execute_arithmetic_number(2, v, reg_number, 7),
-            2 => {
-                self.computer.register[reg_number].0 = 0;
-                self.execute_arithmetic_number(1, v, reg_number, 7)
-            }
-            3 => {
-                self.computer.register[reg_number].0 = 0;
-                self.execute_arithmetic_number(2, v, reg_number, 7)
-            }
-            _ => unimplemented!(),
+        let (op, start) = match ins.get_f() {
+            0 => (1, self.computer.register[reg_number]),
+            1 => (2, self.computer.register[reg_number]),
+            2 => (1, MIXWord(0)),
+            3 => (2, MIXWord(0)),
+            _ => return Err(MixError::InvalidInstruction(ins.word)),
+        };
+        let overflow = self.computer.overflow;
+        let (word, _) = self.arithmetic(ins, op, start, MIXWord(0), v)?;
+        if Registers::width(reg_number) < 5 {
+            // an index register keeps two bytes, or the result is undefined.
+            self.computer.overflow = overflow;
         }
+        self.set_register(reg_number, word)
     }
 
-    fn execute_arithmetic(&mut self, ins: MIXWord) -> Result<(), Box<dyn Error>> {
+    fn execute_arithmetic(&mut self, ins: Instruction) -> Result<(), MixError> {
         let address = self.calculate_address(ins)?;
-        let (left, right) = (ins.get_f() / 8, ins.get_f() % 8);
-        let v = self.computer.memory[address].get_range(left, right);
-        self.execute_arithmetic_number(ins.get_op(), v, 0, 7)
+        let (left, right) = field_spec(ins)?;
+        let v = self
+            .read(address)?
+            .get_range_for(left, right, self.computer.byte_size);
+        let (a, x) = self.arithmetic(
+            ins,
+            ins.get_op(),
+            self.computer.register[0],
+            self.computer.register[7],
+            v,
+        )?;
+        self.set_register(0, a)?;
+        self.set_register(7, x)
     }
 
-    fn execute_arithmetic_number(
+    /// `ADD`, `SUB`, `MUL` or `DIV` (`op` 1 to 4) of `a`, and `x` for `DIV`, by `v`,
+    /// giving the new `a` and `x`. `ins` is the instruction that asked for it.
+    fn arithmetic(
         &mut self,
+        ins: Instruction,
         op: u32,
+        mut a: MIXWord,
+        mut x: MIXWord,
         v: MIXWord,
-        reg_a: usize,
-        reg_x: usize,
-    ) -> Result<(), Box<dyn Error>> {
-        let result: i64;
+    ) -> Result<(MIXWord, MIXWord), MixError> {
+        let size = self.computer.byte_size;
+        let word = size.word_size() as i128;
 
         match op {
             1 | 2 => {
-                if op == 1 {
-                    result = self.computer.register[reg_a].get_value() + v.get_value();
+                let result = if op == 1 {
+                    a.get_value() + v.get_value()
                 } else {
-                    result = self.computer.register[reg_a].get_value() - v.get_value();
-                }
+                    a.get_value() - v.get_value()
+                };
+                let (sum, overflow) = size.wrap(result as i128);
                 if result == 0 {
-                    self.computer.register[reg_a].0 &= 0b10000000000000000000000000000000;
+                    a.set_unsigned(0);
                 } else {
-                    let abs_res = result.abs();
-                    self.computer.register[reg_a].0 = (abs_res & ((1 << 30) - 1)) as u32;
-                    self.computer.register[reg_a].set_opposite(if result < 0 { 1 } else { 0 });
-                    if abs_res > (1 << 30) - 1 {
-                        self.computer.overflow = true;
-                    }
+                    a = sum;
+                }
+                if overflow {
+                    self.computer.overflow = true;
                 }
             }
             3 => {
-                result = self.computer.register[reg_a].get_value() * v.get_value();
-                let abs_res = result.abs();
-                self.computer.register[reg_x].0 = (abs_res & ((1 << 30) - 1)) as u32;
-                self.computer.register[reg_a].0 = (abs_res >> 30) as u32;
-                self.computer.register[reg_a].set_opposite(if result < 0 { 1 } else { 0 });
-                self.computer.register[reg_x].set_opposite(if result < 0 { 1 } else { 0 });
+                let result = a.get_value() as i128 * v.get_value() as i128;
+                let negative = (result < 0) as u32;
+                a = size.wrap(result / word).0;
+                x = size.wrap(result % word).0;
+                a.set_opposite(negative);
+                x.set_opposite(negative);
             }
             4 => {
                 if v.get_value() == 0 {
-                    return Err("divide by 0".into());
+                    return Err(MixError::DivideByZero);
                 }
-                let div = self.computer.register[reg_a].get_value() * (1 << 30)
-                    + self.computer.register[reg_x].get_value().abs();
-                result = div / v.get_value();
-                let remainder = div % v.get_value();
-                if result > (1 << 30) - 1 {
+                // rAX is one number of ten bytes with the sign of rA.
+                let c = a.get_opposite();
+                let magnitude = a.get_unsinged() as i128 * word + x.get_unsinged() as i128;
+                let div = if c == 1 { -magnitude } else { magnitude };
+                let result = div / v.get_value() as i128;
+                let remainder = div % v.get_value() as i128;
+                let (mut quotient, overflow) = size.wrap(result);
+                if overflow {
                     self.computer.overflow = true;
                 }
-                let c = self.computer.register[reg_a].get_opposite();
-                self.computer.register[reg_a].0 = result.unsigned_abs() as u32;
-                self.computer.register[reg_x].0 = remainder.unsigned_abs() as u32;
-                self.computer.register[reg_x].set_opposite(c);
-                self.computer.register[reg_a].set_opposite(if result < 0 { 1 } else { 0 });
+                // the sign is that of rA times that of V, even for a zero quotient.
+                quotient.set_opposite(c ^ v.get_opposite());
+                a = quotient;
+                x = size.wrap(remainder).0;
+                x.set_opposite(c);
             }
-            _ => unreachable!(),
+            _ => return Err(MixError::InvalidInstruction(ins.word)),
         }
 
-        Ok(())
+        Ok((a, x))
     }
 
-    fn calculate_bigm(&self, ins: MIXWord) -> usize {
-        (ins.get_m()
+    fn calculate_m(&self, ins: Instruction) -> i64 {
+        ins.get_m()
             + if ins.get_i() != 0 {
-                self.computer.register[ins.get_i() as usize].0 as i32
+                self.computer.register[ins.get_i() as usize].get_value()
             } else {
-                0i32
-            }) as usize
+                0
+            }
+    }
+
+    fn calculate_address(&self, ins: Instruction) -> Result<i64, MixError> {
+        self.check_address(self.calculate_m(ins))
+    }
+
+    /// a location in memory, or a negative one in control state.
+    fn check_address(&self, address: i64) -> Result<i64, MixError> {
+        let control = self.interrupts.as_ref().is_some_and(|i| i.control);
+        if (0..=MEMORY_MAX as i64).contains(&address)
+            || (control && (-(CONTROL_MEMORY as i64)..0).contains(&address))
+        {
+            Ok(address)
+        } else {
+            Err(MixError::Address(address))
+        }
+    }
+
+    fn word(&self, address: i64) -> Result<MIXWord, MixError> {
+        let address = self.check_address(address)?;
+        match &self.interrupts {
+            Some(interrupts) if address < 0 => Ok(*interrupts.get(address).unwrap()),
+            _ => Ok(self.computer.memory[address as usize]),
+        }
+    }
+
+    fn word_mut(&mut self, address: i64) -> Result<&mut MIXWord, MixError> {
+        let address = self.check_address(address)?;
+        match &mut self.interrupts {
+            Some(interrupts) if address < 0 => Ok(interrupts.get_mut(address).unwrap()),
+            _ => Ok(&mut self.computer.memory[address as usize]),
+        }
+    }
+
+    /// an operand the current instruction reads.
+    fn read(&mut self, address: i64) -> Result<MIXWord, MixError> {
+        let word = self.word(address)?;
+        self.note(address, Access::Read, word, word);
+        Ok(word)
+    }
+
+    fn write(&mut self, address: i64, word: MIXWord) -> Result<(), MixError> {
+        let old = self.word(address)?;
+        *self.word_mut(address)? = word;
+        self.note(address, Access::Write, old, word);
+        self.record(address, old);
+        Ok(())
     }
 
-    fn calculate_address(&self, ins: MIXWord) -> Result<usize, Box<dyn Error>> {
-        let v = self.calculate_bigm(ins);
-        match v {
-            0..=MEMORY_MAX => Ok(v),
-            // 4546 => Ok(4000),
-            _ => Err("Index out of range".into()),
+    /// keep a word the current instruction overwrites, for the journal.
+    fn record(&mut self, address: i64, old: MIXWord) {
+        if self.journal.capacity() > 0 {
+            self.written.push((address, old));
         }
     }
 
-    fn execute_load(&mut self, ins: MIXWord) -> Result<(), Box<dyn Error>> {
+    fn execute_load(&mut self, ins: Instruction) -> Result<(), MixError> {
         // Load Operations
         let address = self.calculate_address(ins)?;
-        let memory_data = self.computer.memory[address];
+        let memory_data = self.read(address)?;
         let (reg_number, oppo) = ((ins.get_op() - 8) % 8, (ins.get_op() - 8) / 8);
-        let (left, right) = (ins.get_f() / 8, ins.get_f() % 8);
+        let (left, right) = field_spec(ins)?;
 
-        self.computer.register[reg_number as usize] = memory_data.get_range(left, right);
+        let mut word = memory_data.get_range_for(left, right, self.computer.byte_size);
         if oppo == 1 {
-            self.computer.register[reg_number as usize]
-                .set_opposite(1 - self.computer.register[reg_number as usize].get_opposite());
+            word.set_opposite(1 - word.get_opposite());
         }
-        Ok(())
+        self.computer
+            .register
+            .set(reg_number as usize, word, self.computer.byte_size)
     }
 
-    fn execute_store(&mut self, ins: MIXWord) -> Result<(), Box<dyn Error>> {
+    fn execute_store(&mut self, ins: Instruction) -> Result<(), MixError> {
         let address = self.calculate_address(ins)?;
 
-        let memory_data = self.computer.memory[address];
+        let memory_data = self.word(address)?;
         let reg_data = if ins.get_op() == 33 {
             0.into()
         } else {
             self.computer.register[(ins.get_op() - 24) as usize]
         };
 
-        let (mut left, right) = (ins.get_f() / 8, ins.get_f() % 8);
-
-        let reg: Vec<u32> = reg_data.into();
-        let mut mem: Vec<u32> = memory_data.into();
-        if left == 0 {
-            mem[0] = reg[0];
-            left += 1;
-        }
-        let mut reg = reg.into_iter().rev();
-        for i in (left..=right).rev() {
-            mem[i as usize] = reg.next().unwrap();
-        }
-
-        self.computer.memory[address] = (mem[0], mem[1], mem[2], mem[3], mem[4], mem[5]).into();
+        let (left, right) = field_spec(ins)?;
+        let mut word = memory_data;
+        word.set_range_for(left, right, reg_data, self.computer.byte_size);
+        self.write(address, word)?;
 
         Ok(())
     }
-
-
 }
@@ -1,7 +1,109 @@
-use serde::{Deserialize, Serialize};
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
-pub struct MIXWord(pub u32);
-pub const MASK: u32 = 0b01111111111111111111111111111111;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::error::Error;
+
+/// a sign and a magnitude; with binary bytes the magnitude holds the five
+/// six-bit bytes side by side.
+///
+/// Migrating from the 32-bit word: the field is a `u64` with the sign in bit 63
+/// rather than a `u32` with the sign in bit 31, since a decimal magnitude needs
+/// 34 bits. Build words with `from_value`, `From<u32>` or the tuple conversions,
+/// and read them with `get_value`, `get_unsinged` and `get_opposite`, instead of
+/// touching `.0`; those keep their meaning for binary words.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MIXWord(pub u64);
+const SIGN: u64 = 1 << 63;
+pub const MASK: u64 = SIGN - 1;
+/// the largest magnitude of a word with binary bytes; see [`ByteSize::max_value`].
+pub const MAX_VALUE: u32 = (1 << 30) - 1;
+
+/// ### Byte size
+/// the number of values a byte can hold. Knuth asks that programs work for
+/// any byte size from 64 to 100, so a machine uses either extreme.
+/// ```rust
+/// use mixe::mixword::ByteSize;
+/// assert_eq!(ByteSize::Binary.max_value(), (1 << 30) - 1);
+/// assert_eq!(ByteSize::Decimal.max_value(), 9_999_999_999);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum ByteSize {
+    #[default]
+    Binary,
+    Decimal,
+}
+
+impl ByteSize {
+    /// b, the values of one byte.
+    pub fn base(self) -> u64 {
+        match self {
+            ByteSize::Binary => 64,
+            ByteSize::Decimal => 100,
+        }
+    }
+
+    /// b^5, one more than the largest magnitude of a word.
+    pub fn word_size(self) -> u64 {
+        self.base().pow(5)
+    }
+
+    pub fn max_value(self) -> u64 {
+        self.word_size() - 1
+    }
+
+    /// ### Wrap
+    /// the word holding `value` modulo b^5, and whether it overflowed.
+    /// ```rust
+    /// use mixe::mixword::ByteSize;
+    /// use mixe::MIXWord;
+    /// let (word, overflow) = ByteSize::Decimal.wrap(-10_000_000_001);
+    /// assert_eq!((word, overflow), (MIXWord::from_value(-1), true));
+    /// ```
+    pub fn wrap(self, value: i128) -> (MIXWord, bool) {
+        let magnitude = value.unsigned_abs();
+        let mut word = MIXWord((magnitude % self.word_size() as u128) as u64);
+        word.set_opposite((value < 0) as u32);
+        (word, magnitude > self.max_value() as u128)
+    }
+}
+
+impl std::str::FromStr for ByteSize {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "64" | "BINARY" => Ok(ByteSize::Binary),
+            "100" | "DECIMAL" => Ok(ByteSize::Decimal),
+            _ => Err(format!("byte size must be 64 or 100, not {}", s).into()),
+        }
+    }
+}
+
+// a word serializes as the 32 bits it used to have, sign in bit 31; larger
+// magnitudes only decimal bytes reach are tagged so they cannot be mistaken for those.
+const WIDE: u64 = 1 << 62;
+
+impl Serialize for MIXWord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match u32::try_from(self.get_unsinged()) {
+            Ok(magnitude) if magnitude >> 31 == 0 => {
+                serializer.serialize_u32(self.get_opposite() << 31 | magnitude)
+            }
+            _ => serializer.serialize_u64(self.0 | WIDE),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MIXWord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = u64::deserialize(deserializer)?;
+        if value & WIDE != 0 {
+            Ok(MIXWord(value & !WIDE))
+        } else {
+            u32::try_from(value)
+                .map(MIXWord::from)
+                .map_err(|_| serde::de::Error::custom("Invalid value for MIXWord"))
+        }
+    }
+}
 
 fn max(l: u32, r: u32) -> u32 {
     if l > r {
@@ -21,62 +123,134 @@ impl MIXWord {
     /// assert_eq!(g, 0b11111101010u32.into());
     /// ```
     pub fn set_op(&mut self, c: u32) {
-        self.0 = ((self.0 >> 6) << 6) + (c & 0b111111);
+        self.0 = ((self.0 >> 6) << 6) + (c as u64 & 0b111111);
     }
     pub fn get_op(&self) -> u32 {
-        self.0 & 0b111111
+        (self.0 & 0b111111) as u32
     }
     pub fn set_f(&mut self, c: u32) {
-        self.0 = (self.0 & 0b11111111111111111111000000111111) + ((c & 0b111111) << 6);
+        self.0 = (self.0 & !(0b111111 << 6)) + ((c as u64 & 0b111111) << 6);
     }
     pub fn get_f(&self) -> u32 {
-        (self.0 >> 6) & 0b111111
+        ((self.0 >> 6) & 0b111111) as u32
     }
     pub fn set_i(&mut self, c: u32) {
-        self.0 = (self.0 & 0b11111111111111000000111111111111) + ((c & 0b111111) << 12);
+        self.0 = (self.0 & !(0b111111 << 12)) + ((c as u64 & 0b111111) << 12);
     }
     pub fn get_i(&self) -> u32 {
-        (self.0 >> 12) & 0b111111
+        ((self.0 >> 12) & 0b111111) as u32
     }
     pub fn set_opposite(&mut self, c: u32) {
-        self.0 = (self.0 & MASK) + ((c & 1) << 31);
+        self.0 = (self.0 & MASK) + ((c as u64 & 1) << 63);
     }
     pub fn get_opposite(&self) -> u32 {
-        self.0 >> 31
+        (self.0 >> 63) as u32
     }
     pub fn set_aa(&mut self, c: u32) {
-        self.0 = (self.0 & 0b11000000000000111111111111111111) + ((c & 0b111111111111) << 18);
+        self.0 = (self.0 & !(0b111111111111 << 18)) + ((c as u64 & 0b111111111111) << 18);
     }
     pub fn get_aa(&self) -> u32 {
-        (self.0 >> 18) & 0b111111111111
+        ((self.0 >> 18) & 0b111111111111) as u32
     }
     /// ## with m
     pub fn get_m(&self) -> i32 {
         (self.get_aa() as i32) * (if self.get_opposite() == 1 { -1 } else { 1 })
     }
+    /// the field (l:r) with binary bytes, as `LDA` loads it.
     pub fn get_range(&self, l: u32, r: u32) -> MIXWord {
+        self.get_range_for(l, r, ByteSize::Binary)
+    }
+    /// ### Get range for
+    /// the field (l:r) with bytes of the given size, as `LDA` loads it.
+    /// ```rust
+    /// use mixe::mixword::ByteSize;
+    /// use mixe::MIXWord;
+    /// let g = MIXWord::from_value_for(-1_02_03_04_05, ByteSize::Decimal);
+    /// assert_eq!(g.get_range_for(0, 2, ByteSize::Decimal).get_value(), -102);
+    /// assert_eq!(g.get_range_for(3, 4, ByteSize::Decimal).get_value(), 304);
+    /// ```
+    pub fn get_range_for(&self, l: u32, r: u32, size: ByteSize) -> MIXWord {
+        let b = size.base();
         let ll = max(l, 1u32);
-        let absolute_part = if r > 0 {
-            (self.0 >> ((5 - r) * 6)) & ((1 << ((r - ll + 1) * 6)) - 1)
+        let mut ret = if r >= ll {
+            MIXWord(self.get_unsinged() / b.pow(5 - r) % b.pow(r - ll + 1))
         } else {
-            0
+            MIXWord(0)
         };
-
-        if l > 0 {
-            absolute_part.into()
-        } else {
-            let mut ret: MIXWord = absolute_part.into();
+        if l == 0 {
             ret.set_opposite(self.get_opposite());
-            ret
         }
+        ret
+    }
+    /// ### Set range
+    /// store the rightmost bytes of `value` into the field (l:r) with binary bytes,
+    /// the way `STA` does.
+    /// ```rust
+    /// use mixe::MIXWord;
+    /// let mut g: MIXWord = (1, 1, 2, 3, 4, 5).into();
+    /// g.set_range(2, 3, (0, 6, 7, 8, 9, 0).into());
+    /// assert_eq!(g, (1, 1, 9, 0, 4, 5).into());
+    /// ```
+    pub fn set_range(&mut self, l: u32, r: u32, value: MIXWord) {
+        self.set_range_for(l, r, value, ByteSize::Binary)
     }
-    pub fn set_unsigned(&mut self, c: u32) {
+    /// store the rightmost bytes of `value` into the field (l:r) with bytes of the
+    /// given size.
+    pub fn set_range_for(&mut self, l: u32, r: u32, value: MIXWord, size: ByteSize) {
+        if l == 0 {
+            self.set_opposite(value.get_opposite());
+        }
+        let l = max(l, 1u32);
+        if r < l {
+            return;
+        }
+        let b = size.base();
+        let (unit, width) = (b.pow(5 - r), b.pow(r - l + 1));
+        let magnitude = self.get_unsinged();
+        let old = magnitude / unit % width;
+        self.set_unsigned(magnitude - old * unit + value.get_unsinged() % width * unit);
+    }
+    /// ### Bytes
+    /// the sign and the five bytes of a word with bytes of the given size.
+    /// ```rust
+    /// use mixe::mixword::ByteSize;
+    /// use mixe::MIXWord;
+    /// let g = MIXWord::from_value(-1_02_03_04_05);
+    /// assert_eq!(g.bytes(ByteSize::Decimal), [1, 1, 2, 3, 4, 5]);
+    /// assert_eq!(MIXWord::from_bytes([1, 1, 2, 3, 4, 5], ByteSize::Decimal), g);
+    /// ```
+    pub fn bytes(&self, size: ByteSize) -> [u32; 6] {
+        let b = size.base();
+        let mut bytes = [self.get_opposite(), 0, 0, 0, 0, 0];
+        for (i, byte) in bytes.iter_mut().enumerate().skip(1) {
+            *byte = (self.get_unsinged() / b.pow(5 - i as u32) % b) as u32;
+        }
+        bytes
+    }
+    /// the word with a sign and five bytes, each less than the byte size.
+    pub fn from_bytes(bytes: [u32; 6], size: ByteSize) -> Self {
+        let magnitude = bytes[1..]
+            .iter()
+            .fold(0, |m, byte| m * size.base() + *byte as u64);
+        let mut ret = MIXWord(magnitude);
+        ret.set_opposite(bytes[0]);
+        ret
+    }
+    /// the word with the same bytes under another byte size, an error if one does not fit.
+    pub fn convert(&self, from: ByteSize, to: ByteSize) -> Result<Self, Box<dyn Error>> {
+        let bytes = self.bytes(from);
+        match bytes[1..].iter().find(|byte| **byte as u64 >= to.base()) {
+            Some(byte) => Err(format!("byte {} does not fit in {} values", byte, to.base()).into()),
+            None => Ok(Self::from_bytes(bytes, to)),
+        }
+    }
+    pub fn set_unsigned(&mut self, c: u64) {
         let o = self.get_opposite();
         self.0 = c & MASK;
         self.set_opposite(o);
     }
     pub fn get_unsinged(&self) -> u64 {
-        (self.0 & MASK) as u64
+        self.0 & MASK
     }
     pub fn get_value(&self) -> i64 {
         (self.get_unsinged() as i64)
@@ -89,16 +263,27 @@ impl MIXWord {
     pub fn into_slice(self) -> (u32, u32, u32, u32, u32, u32) {
         self.into()
     }
+    /// the word holding `c` with binary bytes, its magnitude taken modulo 64^5.
     pub fn from_value(c: i64) -> Self {
-        let mut ret = Self(c.unsigned_abs() as u32);
-        ret.set_opposite(if c > 0 { 0 } else { 1 });
-        ret
+        Self::from_value_for(c, ByteSize::Binary)
+    }
+    /// ### From value for
+    /// the word holding `c` with bytes of the given size, its magnitude taken modulo b^5.
+    /// ```rust
+    /// use mixe::mixword::ByteSize;
+    /// use mixe::MIXWord;
+    /// let g = MIXWord::from_value_for(-5_000_000_000, ByteSize::Decimal);
+    /// assert_eq!(g.get_value(), -5_000_000_000);
+    /// assert_eq!(MIXWord::from_value(1 << 30), MIXWord::from_value(0));
+    /// ```
+    pub fn from_value_for(c: i64, size: ByteSize) -> Self {
+        size.wrap(c as i128).0
     }
 }
 
 impl From<u32> for MIXWord {
     fn from(a: u32) -> Self {
-        MIXWord(a)
+        MIXWord(((a >> 31) as u64) << 63 | (a & 0x7fff_ffff) as u64)
     }
 }
 
@@ -113,8 +298,8 @@ impl From<MIXWord> for (u32, u32, u32, u32, u32, u32) {
     fn from(val: MIXWord) -> Self {
         (
             val.get_opposite(),
-            (val.0 >> 24) & 0b111111,
-            (val.0 >> 18) & 0b111111,
+            ((val.0 >> 24) & 0b111111) as u32,
+            ((val.0 >> 18) & 0b111111) as u32,
             val.get_i(),
             val.get_f(),
             val.get_op(),
@@ -126,8 +311,8 @@ impl From<MIXWord> for Vec<u32> {
     fn from(val: MIXWord) -> Self {
         vec![
             val.get_opposite(),
-            (val.0 >> 24) & 0b111111,
-            (val.0 >> 18) & 0b111111,
+            ((val.0 >> 24) & 0b111111) as u32,
+            ((val.0 >> 18) & 0b111111) as u32,
             val.get_i(),
             val.get_f(),
             val.get_op(),
@@ -139,8 +324,8 @@ impl From<MIXWord> for [u32; 6] {
     fn from(val: MIXWord) -> Self {
         [
             val.get_opposite(),
-            (val.0 >> 24) & 0b111111,
-            (val.0 >> 18) & 0b111111,
+            ((val.0 >> 24) & 0b111111) as u32,
+            ((val.0 >> 18) & 0b111111) as u32,
             val.get_i(),
             val.get_f(),
             val.get_op(),
@@ -150,13 +335,14 @@ impl From<MIXWord> for [u32; 6] {
 
 impl From<(u32, u32, u32, u32, u32, u32)> for MIXWord {
     fn from(a: (u32, u32, u32, u32, u32, u32)) -> Self {
-        MIXWord((a.0 << 31) + (a.1 << 24) + (a.2 << 18) + (a.3 << 12) + (a.4 << 6) + (a.5))
+        [a.0, a.1, a.2, a.3, a.4, a.5].into()
     }
 }
 
 impl From<[u32; 6]> for MIXWord {
     fn from(a: [u32; 6]) -> Self {
-        MIXWord((a[0] << 31) + (a[1] << 24) + (a[2] << 18) + (a[3] << 12) + (a[4] << 6) + (a[5]))
+        let a = a.map(u64::from);
+        MIXWord((a[0] << 63) + (a[1] << 24) + (a[2] << 18) + (a[3] << 12) + (a[4] << 6) + (a[5]))
     }
 }
 
@@ -0,0 +1,132 @@
+use crate::error::MixError;
+use crate::mixword::{ByteSize, MIXWord};
+use serde::{Deserialize, Serialize};
+use std::ops::Index;
+use std::slice::SliceIndex;
+
+pub const A: usize = 0;
+pub const X: usize = 7;
+pub const J: usize = 8;
+
+/// ### Registers
+/// rA, rI1 to rI6, rX and rJ. They can still be read by index 0 to 8 in that
+/// order, but are written only through [`Registers::set`], which checks their
+/// widths. A snapshot keeps them as an array of nine words.
+/// ```rust
+/// use mixe::{MIXComputer, MIXCPU};
+/// let mut computer = MIXCPU::from(MIXComputer::new());
+/// computer.run_command("ENT3 1000").unwrap();
+/// computer.run_command("ENTX -7").unwrap();
+/// let registers = &computer.computer.register;
+/// assert_eq!(registers.i(3).unwrap().get_value(), 1000);
+/// assert_eq!(registers.i(7), None);
+/// assert_eq!(registers.x(), registers[7]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Registers([MIXWord; 9]);
+
+impl Registers {
+    pub fn a(&self) -> MIXWord {
+        self.0[A]
+    }
+
+    /// rI1 to rI6, or `None` for any other `n`.
+    pub fn i(&self, n: usize) -> Option<MIXWord> {
+        (1..=6).contains(&n).then(|| self.0[n])
+    }
+
+    pub fn x(&self) -> MIXWord {
+        self.0[X]
+    }
+
+    pub fn j(&self) -> MIXWord {
+        self.0[J]
+    }
+
+    /// the name of register `r`, as in `rI1`.
+    pub fn name(r: usize) -> String {
+        match r {
+            A => "rA".to_string(),
+            X => "rX".to_string(),
+            J => "rJ".to_string(),
+            n => format!("rI{}", n),
+        }
+    }
+
+    /// the bytes register `r` holds besides its sign: five for rA and rX, two for the others.
+    pub fn width(r: usize) -> u32 {
+        match r {
+            A | X => 5,
+            _ => 2,
+        }
+    }
+
+    /// ### Set
+    /// set register `r` to `word`. An index register or rJ given more than two bytes,
+    /// or rJ given a negative value, would be undefined in TAOCP, so it is an error
+    /// and the register keeps its value. So is a register `r` past rJ.
+    /// ```rust
+    /// use mixe::mixword::ByteSize;
+    /// use mixe::{MIXWord, Registers};
+    /// let mut registers = Registers::default();
+    /// assert!(registers.set(1, MIXWord::from_value(-4095), ByteSize::Binary).is_ok());
+    /// assert!(registers.set(1, MIXWord::from_value(4096), ByteSize::Binary).is_err());
+    /// assert!(registers.set(1, MIXWord::from_value(9999), ByteSize::Decimal).is_ok());
+    /// assert!(registers.set(8, MIXWord::from_value(-1), ByteSize::Binary).is_err());
+    /// assert!(registers.set(9, MIXWord::from_value(0), ByteSize::Binary).is_err());
+    /// assert_eq!(registers.i(1).unwrap().get_value(), 9999);
+    /// ```
+    pub fn set(&mut self, r: usize, word: MIXWord, size: ByteSize) -> Result<(), MixError> {
+        if r > J {
+            return Err(MixError::Undefined(format!("no register {}", r)));
+        }
+        let limit = size.base().pow(Self::width(r));
+        if word.get_unsinged() >= limit || (r == J && word.get_opposite() == 1) {
+            return Err(MixError::Undefined(format!(
+                "{} cannot hold {}",
+                Self::name(r),
+                word.get_value()
+            )));
+        }
+        self.0[r] = word;
+        Ok(())
+    }
+
+    /// save `next`, the location after a jump, in rJ. A jump made in the control
+    /// state may leave from a negative location, and rJ keeps that sign so that
+    /// `STJ` can still return there, so this skips the check [`Registers::set`] makes.
+    pub(crate) fn link(&mut self, next: i64) {
+        self.0[J] = MIXWord::from_value(next);
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, MIXWord> {
+        self.0.iter()
+    }
+}
+
+impl Default for Registers {
+    fn default() -> Self {
+        Registers([MIXWord(0); 9])
+    }
+}
+
+impl From<[MIXWord; 9]> for Registers {
+    fn from(registers: [MIXWord; 9]) -> Self {
+        Registers(registers)
+    }
+}
+
+impl From<Registers> for [MIXWord; 9] {
+    fn from(registers: Registers) -> Self {
+        registers.0
+    }
+}
+
+impl<I: SliceIndex<[MIXWord]>> Index<I> for Registers {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        &self.0[index]
+    }
+}
@@ -1,18 +1,224 @@
+mod card_reader;
+mod disk;
+mod line_printer;
+mod memory;
+mod paper_tape;
+mod tape;
+mod terminal;
+
+use std::any::Any;
+use std::cell::RefCell;
 use std::error::Error;
+use std::fmt::Debug;
+use std::io::{BufRead, Read, Write};
+use std::rc::Rc;
+
+use crate::charset::{from_words, to_words, Unmappable};
+use crate::error::MixError;
+use crate::mixword::MIXWord;
+pub use card_reader::{CardReader, CARD_COLUMNS};
+pub use disk::{Disk, DISK_BLOCK};
+pub use line_printer::LinePrinter;
+pub use memory::MemoryDevice;
+pub use paper_tape::PaperTape;
+pub use tape::{Tape, TAPE_BLOCK, WORD_BYTES};
+pub use terminal::{Terminal, TERMINAL_COLUMNS};
+
+/// Units 0 to 20.
+pub const UNIT_COUNT: usize = 21;
+
+/// read one line of text as a block of `columns` characters, padded with blanks.
+fn read_line_block(
+    input: &mut dyn BufRead,
+    columns: usize,
+    device: &str,
+) -> Result<Vec<MIXWord>, Box<dyn Error>> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Err(format!("{}: no more input", device).into());
+    }
+    let text = line.trim_end_matches(['\n', '\r']);
+    if text.chars().count() > columns {
+        return Err(format!("{}: line longer than {} columns", device, columns).into());
+    }
+    to_words(&format!("{:1$}", text, columns), Unmappable::Error)
+}
+
+/// write a block as one line of text, without trailing blanks.
+fn write_line_block(output: &mut dyn Write, block: &[MIXWord]) -> Result<(), Box<dyn Error>> {
+    let line = from_words(block, Unmappable::Replace('?'))?;
+    writeln!(output, "{}", line.trim_end_matches(' '))?;
+    output.flush()?;
+    Ok(())
+}
+
+/// An in-memory sink whose clones share their contents.
+/// ```rust
+/// use mixe::unit::SharedBuffer;
+/// use std::io::Write;
+/// let buffer = SharedBuffer::default();
+/// write!(buffer.clone(), "HELLO").unwrap();
+/// assert_eq!(buffer.contents(), "HELLO");
+/// ```
+#[derive(Clone, Default, Debug)]
+pub struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl SharedBuffer {
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.borrow()).into_owned()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Standard input taken a line at a time from the buffer `std::io::stdin` keeps, so devices
+/// and the REPL's own `read_line` never swallow input meant for each other.
+#[derive(Default)]
+struct SharedStdin {
+    line: Vec<u8>,
+    position: usize,
+}
+
+impl Read for SharedStdin {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.fill_buf()?.read(buf)?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl BufRead for SharedStdin {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.position == self.line.len() {
+            self.line.clear();
+            self.position = 0;
+            std::io::stdin().lock().read_until(b'\n', &mut self.line)?;
+        }
+        Ok(&self.line[self.position..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.position = (self.position + amt).min(self.line.len());
+    }
+}
+
+/// ### Device
+/// a peripheral that can be attached to a unit. Only `block_size` is required;
+/// a device that cannot read, write or take `IOC` reports an error by default.
+/// ```rust
+/// use mixe::unit::Device;
+/// use mixe::{MIXComputer, MIXWord, MIXCPU};
+/// use std::error::Error;
+///
+/// #[derive(Debug, Default)]
+/// struct Plotter {
+///     points: Vec<i64>,
+/// }
+///
+/// impl Device for Plotter {
+///     fn block_size(&self) -> usize {
+///         1
+///     }
+///
+///     fn write_block(&mut self, block: &[MIXWord], _x: i64) -> Result<(), Box<dyn Error>> {
+///         self.points.push(block[0].get_value());
+///         Ok(())
+///     }
+/// }
+///
+/// let mut computer = MIXCPU::from(MIXComputer::new());
+/// computer.computer.units[17].attach(Box::new(Plotter::default()));
+/// computer.computer.memory[100] = MIXWord::from_value(42);
+/// computer.run_command("OUT 100(17)").unwrap();
+/// let plotter = computer.computer.units[17].device::<Plotter>().unwrap();
+/// assert_eq!(plotter.points, vec![42]);
+/// ```
+pub trait Device: Any + Debug {
+    /// words moved by one `IN` or `OUT`.
+    fn block_size(&self) -> usize;
+
+    /// `IN`: the next block; `x` is the value of rX.
+    fn read_block(&mut self, _x: i64) -> Result<Vec<MIXWord>, Box<dyn Error>> {
+        Err(format!("{:?} is output only", self).into())
+    }
 
-use crate::MIXComputer;
-use serde::{Deserialize, Serialize};
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize, Serialize)]
+    /// `OUT`: write one block; `x` is the value of rX.
+    fn write_block(&mut self, _block: &[MIXWord], _x: i64) -> Result<(), Box<dyn Error>> {
+        Err(format!("{:?} is input only", self).into())
+    }
+
+    /// `IOC M`; `x` is the value of rX.
+    fn control(&mut self, m: i64, _x: i64) -> Result<(), Box<dyn Error>> {
+        Err(format!("IOC {} is undefined for {:?}", m, self).into())
+    }
+
+    /// how long, in `u`, the unit stays busy after each `IN`, `OUT` or `IOC`.
+    fn operation_time(&self) -> u64 {
+        0
+    }
+
+    /// whether the device is busy for reasons of its own, for `JBUS` and `JRED`.
+    fn busy(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug)]
 pub struct Unit {
     id: u32,
+    device: Option<Box<dyn Device>>,
+    busy_until: u64,
 }
 
 impl Unit {
     pub fn new(id: u32) -> Self {
-        Unit { id }
+        let device: Option<Box<dyn Device>> = match id {
+            16 => Some(Box::new(CardReader::stdin())),
+            18 => Some(Box::new(LinePrinter::stdout())),
+            19 => Some(Box::new(Terminal::stdio())),
+            _ => None,
+        };
+        Unit {
+            id,
+            device,
+            busy_until: 0,
+        }
+    }
+
+    /// ### Attach
+    /// replace the device of this unit.
+    pub fn attach(&mut self, device: Box<dyn Device>) {
+        self.device = Some(device);
+    }
+
+    /// remove the device, leaving the unit unattached.
+    pub fn detach(&mut self) -> Option<Box<dyn Device>> {
+        self.device.take()
+    }
+
+    /// the attached device, if it is a `T`.
+    pub fn device<T: Device>(&self) -> Option<&T> {
+        let device: &dyn Any = self.device.as_deref()?;
+        device.downcast_ref()
+    }
+
+    pub fn device_mut<T: Device>(&mut self) -> Option<&mut T> {
+        let device: &mut dyn Any = self.device.as_deref_mut()?;
+        device.downcast_mut()
     }
 
     pub fn get_block_size(&self) -> Option<u32> {
+        if let Some(device) = &self.device {
+            return Some(device.block_size() as u32);
+        }
         match self.id {
             0..=15 => Some(100),
             16..=17 => Some(16),
@@ -22,18 +228,94 @@ impl Unit {
         }
     }
 
-    pub fn unit_in(&mut self, _start: usize) -> Result<(), Box<dyn Error>> {
-        println!("unit number {} in", self.id);
-        println!("not yet implemented.");
-        Ok(())
+    /// whether the unit is still working at time `now`.
+    pub fn busy(&self, now: u64) -> bool {
+        now < self.busy_until || self.device.as_ref().is_some_and(|device| device.busy())
+    }
+
+    /// the time at which the last operation finishes.
+    pub fn ready_at(&self) -> u64 {
+        self.busy_until
+    }
+
+    /// start an operation at time `now`.
+    pub(crate) fn occupy(&mut self, now: u64) {
+        let time = self
+            .device
+            .as_ref()
+            .map_or(0, |device| device.operation_time());
+        self.busy_until = now + time;
     }
 
-    pub fn unit_out(&self, start: usize, computer: &MIXComputer) -> Result<(), Box<dyn Error>> {
-        println!("unit number {}", self.id);
-        let size = self.get_block_size().ok_or("Unit invalid.")? as usize;
-        for i in start..(start + size) {
-            println!("[INFO] unit {} : {}", self.id, computer.memory[i].0);
+    pub(crate) fn release(&mut self) {
+        self.busy_until = 0;
+    }
+
+    /// ### In
+    /// read a block into memory at `start`; a disk reads the block numbered `x`, the value of rX.
+    pub fn unit_in(
+        &mut self,
+        start: usize,
+        memory: &mut [MIXWord],
+        x: i64,
+    ) -> Result<(), MixError> {
+        let id = self.id as usize;
+        let size = self
+            .get_block_size()
+            .ok_or_else(|| MixError::device(id, "no such unit"))? as usize;
+        let block = memory
+            .get_mut(start..start + size)
+            .ok_or(MixError::Address((start + size - 1) as i64))?;
+        match &mut self.device {
+            Some(device) => {
+                let data = device.read_block(x).map_err(|e| MixError::device(id, e))?;
+                if data.len() != size {
+                    return Err(MixError::device(
+                        id,
+                        format!("read {} words instead of {}", data.len(), size),
+                    ));
+                }
+                block.copy_from_slice(&data);
+            }
+            None => {
+                println!("unit number {} in", self.id);
+                println!("not yet implemented.");
+            }
         }
         Ok(())
     }
+
+    pub fn unit_out(&mut self, start: usize, memory: &[MIXWord], x: i64) -> Result<(), MixError> {
+        let id = self.id as usize;
+        let size = self
+            .get_block_size()
+            .ok_or_else(|| MixError::device(id, "no such unit"))? as usize;
+        let block = memory
+            .get(start..start + size)
+            .ok_or(MixError::Address((start + size - 1) as i64))?;
+        match &mut self.device {
+            Some(device) => device
+                .write_block(block, x)
+                .map_err(|e| MixError::device(id, e)),
+            None => {
+                println!("unit number {}", self.id);
+                for word in block {
+                    println!("[INFO] unit {} : {}", self.id, word.0);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// ### Control
+    /// `IOC M(U)`; `IOC 0(18)` starts a new page, a tape rewinds or skips blocks,
+    /// `IOC 0` positions a disk arm at block `x`, and `IOC 0(20)` rewinds the paper tape.
+    pub fn control(&mut self, m: i64, x: i64) -> Result<(), MixError> {
+        match &mut self.device {
+            Some(device) => device
+                .control(m, x)
+                .map_err(|e| MixError::device(self.id as usize, e)),
+            None => Ok(()),
+        }
+    }
 }
@@ -0,0 +1,52 @@
+use super::{read_line_block, Device, SharedStdin};
+use crate::mixword::MIXWord;
+use std::error::Error;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+/// Columns on a card.
+pub const CARD_COLUMNS: usize = 80;
+
+/// Unit 16: reads one line of text per card.
+pub struct CardReader {
+    input: Box<dyn BufRead>,
+}
+
+impl Debug for CardReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CardReader").finish_non_exhaustive()
+    }
+}
+
+impl CardReader {
+    pub fn new(input: impl BufRead + 'static) -> Self {
+        CardReader {
+            input: Box::new(input),
+        }
+    }
+
+    pub fn stdin() -> Self {
+        Self::new(SharedStdin::default())
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl Device for CardReader {
+    fn block_size(&self) -> usize {
+        CARD_COLUMNS / 5
+    }
+
+    fn operation_time(&self) -> u64 {
+        10_000
+    }
+
+    /// read the next card as 16 words; short lines are padded with blanks.
+    fn read_block(&mut self, _x: i64) -> Result<Vec<MIXWord>, Box<dyn Error>> {
+        read_line_block(&mut self.input, CARD_COLUMNS, "card reader")
+    }
+}
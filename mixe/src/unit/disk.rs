@@ -0,0 +1,78 @@
+use super::tape::{decode_block, encode_block, WORD_BYTES};
+use super::Device;
+use crate::mixword::MIXWord;
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Words in a disk block.
+pub const DISK_BLOCK: usize = 100;
+
+/// Units 8-15: a disk or drum image file whose blocks are addressed by rX.
+#[derive(Debug)]
+pub struct Disk {
+    file: File,
+    arm: u64,
+}
+
+impl Disk {
+    /// open a disk image, creating an empty disk if the file does not exist.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        Ok(Disk { file, arm: 0 })
+    }
+
+    /// the block the arm was last positioned at.
+    pub fn arm(&self) -> u64 {
+        self.arm
+    }
+
+    fn seek(&mut self, x: i64) -> Result<(), Box<dyn Error>> {
+        let block = u64::try_from(x).map_err(|_| format!("disk: no block {}", x))?;
+        self.file
+            .seek(SeekFrom::Start(block * (DISK_BLOCK * WORD_BYTES) as u64))?;
+        self.arm = block;
+        Ok(())
+    }
+}
+
+impl Device for Disk {
+    fn block_size(&self) -> usize {
+        DISK_BLOCK
+    }
+
+    fn operation_time(&self) -> u64 {
+        1_000
+    }
+
+    /// read block `x`; blocks never written read as zeros.
+    fn read_block(&mut self, x: i64) -> Result<Vec<MIXWord>, Box<dyn Error>> {
+        self.seek(x)?;
+        let mut data = Vec::with_capacity(DISK_BLOCK * WORD_BYTES);
+        (&mut self.file)
+            .take((DISK_BLOCK * WORD_BYTES) as u64)
+            .read_to_end(&mut data)?;
+        data.resize(DISK_BLOCK * WORD_BYTES, 0);
+        Ok(decode_block(&data))
+    }
+
+    fn write_block(&mut self, block: &[MIXWord], x: i64) -> Result<(), Box<dyn Error>> {
+        self.seek(x)?;
+        self.file.write_all(&encode_block(block))?;
+        Ok(())
+    }
+
+    /// `IOC 0` positions the arm at block `x`.
+    fn control(&mut self, m: i64, x: i64) -> Result<(), Box<dyn Error>> {
+        if m != 0 {
+            return Err(format!("IOC {} is undefined for a disk", m).into());
+        }
+        self.seek(x)
+    }
+}
@@ -0,0 +1,59 @@
+use super::{write_line_block, Device};
+use crate::mixword::MIXWord;
+use std::error::Error;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Unit 18: prints 120 characters per line.
+pub struct LinePrinter {
+    output: Box<dyn Write>,
+}
+
+impl Debug for LinePrinter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LinePrinter").finish_non_exhaustive()
+    }
+}
+
+impl LinePrinter {
+    pub fn new(output: impl Write + 'static) -> Self {
+        LinePrinter {
+            output: Box::new(output),
+        }
+    }
+
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl Device for LinePrinter {
+    fn block_size(&self) -> usize {
+        24
+    }
+
+    fn operation_time(&self) -> u64 {
+        10_000
+    }
+
+    /// print 24 words as one line, without trailing blanks.
+    fn write_block(&mut self, block: &[MIXWord], _x: i64) -> Result<(), Box<dyn Error>> {
+        write_line_block(&mut self.output, block)
+    }
+
+    /// `IOC 0` skips to the top of the next page with a form feed.
+    fn control(&mut self, m: i64, _x: i64) -> Result<(), Box<dyn Error>> {
+        if m != 0 {
+            return Err(format!("IOC {} is undefined for the line printer", m).into());
+        }
+        write!(self.output, "\x0c")?;
+        self.output.flush()?;
+        Ok(())
+    }
+}
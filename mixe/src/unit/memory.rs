@@ -0,0 +1,139 @@
+use super::Device;
+use crate::charset::{from_words, to_words, Unmappable, WORD_CHARS};
+use crate::mixword::MIXWord;
+use std::collections::VecDeque;
+use std::error::Error;
+
+/// ### Memory device
+/// a device for tests: `IN` takes preloaded blocks in order, while `OUT` blocks and
+/// `IOC` codes are captured for inspection. It fits any unit.
+/// ```rust
+/// use mixe::unit::MemoryDevice;
+/// use mixe::{MIXComputer, MIXCPU};
+/// let mut computer = MIXCPU::from(MIXComputer::new());
+/// let cards = MemoryDevice::from_text(16, &["HELLO"]).unwrap();
+/// computer.computer.units[16].attach(Box::new(cards));
+/// computer.computer.units[18].attach(Box::new(MemoryDevice::new(24)));
+/// computer.run_command("IN 100(16)").unwrap();
+/// computer.run_command("OUT 100(18)").unwrap();
+/// let printer = computer.computer.units[18].device::<MemoryDevice>().unwrap();
+/// printer.assert_lines(&["HELLO"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryDevice {
+    block_size: usize,
+    input: VecDeque<Vec<MIXWord>>,
+    output: Vec<Vec<MIXWord>>,
+    controls: Vec<i64>,
+    operation_time: u64,
+}
+
+impl MemoryDevice {
+    /// a device with no input, moving `block_size` words at a time.
+    pub fn new(block_size: usize) -> Self {
+        MemoryDevice {
+            block_size,
+            ..Default::default()
+        }
+    }
+
+    /// a device whose input is `blocks`; each must hold `block_size` words.
+    pub fn with_blocks(
+        block_size: usize,
+        blocks: Vec<Vec<MIXWord>>,
+    ) -> Result<Self, Box<dyn Error>> {
+        if let Some(block) = blocks.iter().find(|block| block.len() != block_size) {
+            return Err(format!("block of {} words, expected {}", block.len(), block_size).into());
+        }
+        Ok(MemoryDevice {
+            input: blocks.into(),
+            ..Self::new(block_size)
+        })
+    }
+
+    /// a device whose input is one block per line of text, padded with blanks.
+    pub fn from_text(block_size: usize, lines: &[&str]) -> Result<Self, Box<dyn Error>> {
+        let columns = block_size * WORD_CHARS;
+        let blocks = lines
+            .iter()
+            .map(|line| {
+                if line.chars().count() > columns {
+                    return Err(format!("line longer than {} columns: {}", columns, line).into());
+                }
+                to_words(&format!("{:1$}", line, columns), Unmappable::Error)
+            })
+            .collect::<Result<_, Box<dyn Error>>>()?;
+        Self::with_blocks(block_size, blocks)
+    }
+
+    /// keep the unit busy for `time` u after each operation.
+    pub fn with_operation_time(mut self, time: u64) -> Self {
+        self.operation_time = time;
+        self
+    }
+
+    /// the input blocks not read yet.
+    pub fn remaining(&self) -> usize {
+        self.input.len()
+    }
+
+    /// every block written, in order.
+    pub fn blocks(&self) -> &[Vec<MIXWord>] {
+        &self.output
+    }
+
+    /// the `M` of every `IOC`, in order.
+    pub fn controls(&self) -> &[i64] {
+        &self.controls
+    }
+
+    /// every block written, decoded as a line of text without trailing blanks.
+    pub fn lines(&self) -> Vec<String> {
+        self.output
+            .iter()
+            .map(|block| {
+                let line = from_words(block, Unmappable::Replace('?')).unwrap_or_default();
+                line.trim_end_matches(' ').to_string()
+            })
+            .collect()
+    }
+
+    pub fn assert_lines(&self, expected: &[&str]) {
+        assert_eq!(self.lines(), expected, "lines written to the device");
+    }
+
+    pub fn assert_blocks(&self, expected: &[Vec<MIXWord>]) {
+        assert_eq!(self.blocks(), expected, "blocks written to the device");
+    }
+
+    /// check that the program read every input block.
+    pub fn assert_consumed(&self) {
+        assert_eq!(self.remaining(), 0, "input blocks left unread");
+    }
+}
+
+impl Device for MemoryDevice {
+    fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    fn operation_time(&self) -> u64 {
+        self.operation_time
+    }
+
+    fn read_block(&mut self, _x: i64) -> Result<Vec<MIXWord>, Box<dyn Error>> {
+        self.input
+            .pop_front()
+            .ok_or_else(|| "memory device: no more input".into())
+    }
+
+    fn write_block(&mut self, block: &[MIXWord], _x: i64) -> Result<(), Box<dyn Error>> {
+        self.output.push(block.to_vec());
+        Ok(())
+    }
+
+    fn control(&mut self, m: i64, _x: i64) -> Result<(), Box<dyn Error>> {
+        self.controls.push(m);
+        Ok(())
+    }
+}
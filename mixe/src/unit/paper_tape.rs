@@ -0,0 +1,44 @@
+use super::terminal::TERMINAL_COLUMNS;
+use super::{read_line_block, Device};
+use crate::mixword::MIXWord;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader, Seek};
+use std::path::Path;
+
+/// Unit 20: a paper tape reader over a text file, one line of 70 characters per block.
+#[derive(Debug)]
+pub struct PaperTape {
+    input: BufReader<File>,
+}
+
+impl PaperTape {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(PaperTape {
+            input: BufReader::new(File::open(path)?),
+        })
+    }
+}
+
+impl Device for PaperTape {
+    fn block_size(&self) -> usize {
+        TERMINAL_COLUMNS / 5
+    }
+
+    fn operation_time(&self) -> u64 {
+        5_000
+    }
+
+    fn read_block(&mut self, _x: i64) -> Result<Vec<MIXWord>, Box<dyn Error>> {
+        read_line_block(&mut self.input, TERMINAL_COLUMNS, "paper tape")
+    }
+
+    /// `IOC 0` rewinds the tape.
+    fn control(&mut self, m: i64, _x: i64) -> Result<(), Box<dyn Error>> {
+        if m != 0 {
+            return Err(format!("IOC {} is undefined for paper tape", m).into());
+        }
+        self.input.rewind()?;
+        Ok(())
+    }
+}
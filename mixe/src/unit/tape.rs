@@ -0,0 +1,123 @@
+use super::Device;
+use crate::mixword::MIXWord;
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Words in a tape block.
+pub const TAPE_BLOCK: usize = 100;
+/// Bytes a word takes in an image file: the sign, then the five bytes.
+pub const WORD_BYTES: usize = 6;
+
+pub(crate) fn encode_block(block: &[MIXWord]) -> Vec<u8> {
+    block
+        .iter()
+        .flat_map(|word| {
+            let bytes: [u32; 6] = (*word).into();
+            bytes.map(|b| b as u8)
+        })
+        .collect()
+}
+
+pub(crate) fn decode_block(data: &[u8]) -> Vec<MIXWord> {
+    data.chunks(WORD_BYTES)
+        .map(|bytes| {
+            let mut word = [0u32; 6];
+            for (i, b) in bytes.iter().enumerate() {
+                word[i] = *b as u32;
+            }
+            word.into()
+        })
+        .collect()
+}
+
+/// Units 0-7: a tape image file of 100-word blocks, read and written in sequence.
+#[derive(Debug)]
+pub struct Tape {
+    file: File,
+    position: u64,
+}
+
+impl Tape {
+    /// open a tape image, creating an empty tape if the file does not exist.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        Ok(Tape { file, position: 0 })
+    }
+
+    /// the block under the head, counting from 0.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// the number of blocks written on the tape.
+    pub fn len(&self) -> Result<u64, Box<dyn Error>> {
+        Ok(self.file.metadata()?.len() / (TAPE_BLOCK * WORD_BYTES) as u64)
+    }
+
+    pub fn is_empty(&self) -> Result<bool, Box<dyn Error>> {
+        Ok(self.len()? == 0)
+    }
+
+    fn seek(&mut self) -> Result<(), Box<dyn Error>> {
+        self.file.seek(SeekFrom::Start(
+            self.position * (TAPE_BLOCK * WORD_BYTES) as u64,
+        ))?;
+        Ok(())
+    }
+}
+
+impl Device for Tape {
+    fn block_size(&self) -> usize {
+        TAPE_BLOCK
+    }
+
+    fn operation_time(&self) -> u64 {
+        1_000
+    }
+
+    fn read_block(&mut self, _x: i64) -> Result<Vec<MIXWord>, Box<dyn Error>> {
+        let mut data = vec![0u8; TAPE_BLOCK * WORD_BYTES];
+        self.seek()?;
+        match self.file.read_exact(&mut data) {
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                return Err("tape: read past the end of the tape".into())
+            }
+            result => result?,
+        }
+        self.position += 1;
+        Ok(decode_block(&data))
+    }
+
+    /// ### Write block
+    /// write at the head; like a real tape, everything after the block is lost.
+    fn write_block(&mut self, block: &[MIXWord], _x: i64) -> Result<(), Box<dyn Error>> {
+        self.seek()?;
+        self.file.write_all(&encode_block(block))?;
+        self.position += 1;
+        self.file
+            .set_len(self.position * (TAPE_BLOCK * WORD_BYTES) as u64)?;
+        Ok(())
+    }
+
+    /// ### Control
+    /// `IOC 0` rewinds; `IOC M` skips M blocks forward, or -M blocks backward.
+    fn control(&mut self, m: i64, _x: i64) -> Result<(), Box<dyn Error>> {
+        if m == 0 {
+            self.position = 0;
+        } else if m < 0 {
+            self.position = self.position.saturating_sub(m.unsigned_abs());
+        } else if self.position + m as u64 > self.len()? {
+            return Err("tape: skipped past the end of the tape".into());
+        } else {
+            self.position += m as u64;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use super::{read_line_block, write_line_block, Device, SharedStdin};
+use crate::mixword::MIXWord;
+use std::error::Error;
+use std::fmt::Debug;
+use std::io::{self, BufRead, Write};
+
+/// Characters in a terminal or paper tape block.
+pub const TERMINAL_COLUMNS: usize = 70;
+
+/// Unit 19: a typewriter terminal, one line of 70 characters per block.
+pub struct Terminal {
+    input: Box<dyn BufRead>,
+    output: Box<dyn Write>,
+}
+
+impl Debug for Terminal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Terminal").finish_non_exhaustive()
+    }
+}
+
+impl Terminal {
+    pub fn new(input: impl BufRead + 'static, output: impl Write + 'static) -> Self {
+        Terminal {
+            input: Box::new(input),
+            output: Box::new(output),
+        }
+    }
+
+    pub fn stdio() -> Self {
+        Self::new(SharedStdin::default(), io::stdout())
+    }
+}
+
+impl Device for Terminal {
+    fn block_size(&self) -> usize {
+        TERMINAL_COLUMNS / 5
+    }
+
+    /// read the line the user types as 14 words.
+    fn read_block(&mut self, _x: i64) -> Result<Vec<MIXWord>, Box<dyn Error>> {
+        self.output.flush()?;
+        read_line_block(&mut self.input, TERMINAL_COLUMNS, "terminal")
+    }
+
+    fn write_block(&mut self, block: &[MIXWord], _x: i64) -> Result<(), Box<dyn Error>> {
+        write_line_block(&mut self.output, block)
+    }
+}
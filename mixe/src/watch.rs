@@ -0,0 +1,174 @@
+use crate::error::MixError;
+use crate::mixcpu::MEMORY_RANGE;
+use crate::mixword::MIXWord;
+use crate::registers::{Registers, A, J, X};
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+
+/// ### Watch
+/// what a watchpoint looks at. Parsed from `2000`, `2000-2009`, `rA`, `rI1` to `rI6`,
+/// `rX`, `rJ`, `OV` for the overflow toggle or `CI` for the comparison indicator.
+/// ```rust
+/// use mixe::watch::Watch;
+/// assert_eq!("2000-2009".parse::<Watch>().unwrap(), Watch::Memory(2000..=2009));
+/// assert_eq!("rI3".parse::<Watch>().unwrap(), Watch::Register(3));
+/// assert!("4000".parse::<Watch>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Watch {
+    Memory(RangeInclusive<usize>),
+    /// register `r`, numbered as in [`Registers`].
+    Register(usize),
+    Overflow,
+    Comparison,
+}
+
+impl Watch {
+    fn covers(&self, other: &Watch) -> bool {
+        match (self, other) {
+            (Watch::Memory(range), Watch::Memory(address)) => range.contains(address.start()),
+            _ => self == other,
+        }
+    }
+}
+
+impl std::str::FromStr for Watch {
+    type Err = MixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || MixError::Parse(format!("cannot watch {}", s));
+        match s {
+            "rA" => return Ok(Watch::Register(A)),
+            "rX" => return Ok(Watch::Register(X)),
+            "rJ" => return Ok(Watch::Register(J)),
+            "OV" => return Ok(Watch::Overflow),
+            "CI" => return Ok(Watch::Comparison),
+            _ => {}
+        }
+        if let Some(n) = s.strip_prefix("rI") {
+            return match n.parse::<usize>()? {
+                n @ 1..=6 => Ok(Watch::Register(n)),
+                _ => Err(invalid()),
+            };
+        }
+        let (left, right) = s.split_once('-').unwrap_or((s, s));
+        let (left, right): (usize, usize) = (left.trim().parse()?, right.trim().parse()?);
+        if left > right || !MEMORY_RANGE.contains(&right) {
+            return Err(invalid());
+        }
+        Ok(Watch::Memory(left..=right))
+    }
+}
+
+impl Display for Watch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Watch::Memory(range) if range.start() == range.end() => {
+                write!(f, "{}", range.start())
+            }
+            Watch::Memory(range) => write!(f, "{}-{}", range.start(), range.end()),
+            Watch::Register(r) => write!(f, "{}", Registers::name(*r)),
+            Watch::Overflow => write!(f, "OV"),
+            Watch::Comparison => write!(f, "CI"),
+        }
+    }
+}
+
+/// ### Watchpoint
+/// stop a run after an instruction writes what `watch` looks at. A memory watchpoint
+/// stops at every write, even of the same value, and with `read` at every read of an
+/// operand too; registers and the indicators stop when an instruction changes them.
+/// ```rust
+/// use mixe::mixcpu::RunOutcome;
+/// use mixe::watch::{Value, Watch, Watchpoint};
+/// use mixe::{MIXComputer, MIXCPU, MIXWord};
+/// let mut computer = MIXCPU::from(MIXComputer::new());
+/// computer.computer.memory[0] = (0, 0, 3, 0, 2, 49).into(); // ENT1 3
+/// computer.computer.memory[1] = (0, 0, 100, 0, 5, 25).into(); // ST1 100
+/// computer.watchpoints.push(Watchpoint::new(Watch::Memory(100..=109)));
+/// let RunOutcome::Watchpoint(hits) = computer.run() else { panic!() };
+/// assert_eq!(hits[0].location, 1);
+/// assert_eq!(hits[0].old, Value::Word(MIXWord::from_value(0)));
+/// assert_eq!(hits[0].new, Value::Word(MIXWord::from_value(3)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub watch: Watch,
+    /// also stop when memory is read.
+    pub read: bool,
+}
+
+impl Watchpoint {
+    pub fn new(watch: Watch) -> Self {
+        Watchpoint { watch, read: false }
+    }
+
+    /// stop when memory is read too.
+    pub fn reads(mut self) -> Self {
+        self.read = true;
+        self
+    }
+
+    pub(crate) fn triggers(&self, watch: &Watch, access: Access) -> bool {
+        (access == Access::Write || self.read) && self.watch.covers(watch)
+    }
+}
+
+impl Display for Watchpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.watch, if self.read { " READ" } else { "" })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// a value a watchpoint saw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    Word(MIXWord),
+    Toggle(bool),
+    Indicator(Ordering),
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Word(word) => write!(f, "{}", word.get_value()),
+            Value::Toggle(on) => write!(f, "{}", if *on { "on" } else { "off" }),
+            Value::Indicator(ordering) => write!(f, "{:?}", ordering),
+        }
+    }
+}
+
+/// a watched word or indicator the instruction at `location` read or wrote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hit {
+    pub location: i64,
+    /// a single location for memory.
+    pub watch: Watch,
+    pub access: Access,
+    pub old: Value,
+    pub new: Value,
+}
+
+impl Display for Hit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.access {
+            Access::Read => write!(
+                f,
+                "{} read at location {}: {}",
+                self.watch, self.location, self.new
+            ),
+            Access::Write => write!(
+                f,
+                "{} written at location {}: {} -> {}",
+                self.watch, self.location, self.old, self.new
+            ),
+        }
+    }
+}
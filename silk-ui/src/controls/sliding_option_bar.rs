@@ -41,7 +41,7 @@ impl SlidingOptionBar {
         let (hint_rect, option_rects, hint_text_size, option_text_size, offset_y) =
             Self::measure_options(start_pos, hint_text, &options, target_height);
 
-        let initial_rect = option_rects[initial_index].clone();
+        let initial_rect = option_rects[initial_index];
         let bar = Fluent::new(Capsule::new(
             initial_rect.x - PADDING,
             initial_rect.y - PADDING,
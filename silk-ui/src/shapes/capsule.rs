@@ -23,7 +23,7 @@ impl Capsule {
         }
     }
 
-    fn center_in(&mut self, left: f32, top: f32) {
+    pub fn center_in(&mut self, left: f32, top: f32) {
         self.left = left - self.width / 2.;
         self.top = top - self.height / 2.;
     }
@@ -9,7 +9,7 @@ pub struct Line {
 }
 
 impl Line {
-    fn from(a: Vec2, b: Vec2, thickness: f32, color: Color) -> Self {
+    pub fn from(a: Vec2, b: Vec2, thickness: f32, color: Color) -> Self {
         Self {
             pos: vec4(a.x, a.y, b.x, b.y),
             thickness,